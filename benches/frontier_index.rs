@@ -0,0 +1,39 @@
+// compares the frontier index against a linear frontier scan, run with
+// `cargo bench --bench frontier_index`
+use color_shredder::engine::{Engine, Metric, PlacementScore, Settings};
+use std::time::Instant;
+
+fn time_run(settings: &Settings, use_index: bool) -> (f64, Vec<u8>) {
+    let mut engine = Engine::new(settings.clone()).unwrap();
+    engine.set_frontier_index(use_index);
+    let start = Instant::now();
+    engine.run();
+    (start.elapsed().as_secs_f64(), engine.pixels().to_vec())
+}
+
+fn main() {
+    for &size in &[64, 128] {
+        for &metric in &[Metric::Rgb, Metric::Oklab] {
+            let settings = Settings {
+                metric,
+                score: PlacementScore::AverageNeighbor,
+                ..Settings::new(size, size, 1)
+            };
+            let (linear_time, linear_pixels) = time_run(&settings, false);
+            let (index_time, index_pixels) = time_run(&settings, true);
+            assert!(linear_pixels == index_pixels, "index changed the image");
+
+            let cells = (size * size) as f64;
+            println!(
+                "{0}x{0} {1:<6} linear {2:>8.3}s ({3:>9.0}/s)  index {4:>8.3}s ({5:>9.0}/s)  {6:>6.1}x",
+                size,
+                metric.to_string(),
+                linear_time,
+                cells / linear_time,
+                index_time,
+                cells / index_time,
+                linear_time / index_time
+            );
+        }
+    }
+}
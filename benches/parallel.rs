@@ -0,0 +1,58 @@
+// compares the parallel placement modes against a serial run, run with
+// `cargo bench --features parallel --bench parallel`
+use color_shredder::engine::{Engine, Metric, Parallelism, PlacementScore, Settings};
+use std::time::Instant;
+
+fn time_run(settings: &Settings, parallelism: Parallelism) -> (f64, Vec<u8>) {
+    let mut engine = Engine::new(settings.clone()).unwrap();
+    engine.set_parallelism(parallelism).unwrap();
+    let start = Instant::now();
+    engine.run();
+    (start.elapsed().as_secs_f64(), engine.pixels().to_vec())
+}
+
+fn main() {
+    println!("{} threads", rayon::current_num_threads());
+    let variants = vec![
+        // linear frontier scans
+        (128, Metric::Oklab, PlacementScore::MinNeighbor),
+        (128, Metric::Ciede2000, PlacementScore::AverageNeighbor),
+        // frontier index queries
+        (256, Metric::Rgb, PlacementScore::AverageNeighbor),
+    ];
+    let modes = [
+        Parallelism::Exact,
+        Parallelism::Batched(64),
+        Parallelism::Batched(256),
+    ];
+    for (size, metric, score) in variants {
+        let settings = Settings {
+            metric,
+            score,
+            ..Settings::new(size, size, 1)
+        };
+        let (serial_time, serial_pixels) = time_run(&settings, Parallelism::Serial);
+        let cells = (size * size) as f64;
+        println!(
+            "{0}x{0} {1} {2}: serial {3:>8.3}s ({4:>9.0}/s)",
+            size,
+            metric,
+            score,
+            serial_time,
+            cells / serial_time
+        );
+        for &mode in &modes {
+            let (time, pixels) = time_run(&settings, mode);
+            if mode == Parallelism::Exact {
+                assert!(pixels == serial_pixels, "exact mode changed the image");
+            }
+            println!(
+                "  {:<14} {:>8.3}s ({:>9.0}/s)  {:>6.1}x",
+                mode.to_string(),
+                time,
+                cells / time,
+                serial_time / time
+            );
+        }
+    }
+}
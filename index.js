@@ -17,8 +17,226 @@ color_shredder.then(wasm => {
     const FRAMETIME_LIMIT = 1000.0 / 30.0;
     var previous_time = -1;
 
+    // milliseconds of placement work per frame, ?steps=N places a fixed count instead
+    const STEP_BUDGET_MS = 12.0;
+
+    // seed and settings from url parameters so a run can be reproduced
+    const url_params = new URLSearchParams(window.location.search);
+    const url_seed = url_params.get('seed');
+    const seed = url_seed !== null ? BigInt(url_seed) : BigInt(Math.floor(Math.random() * 2 ** 32));
+    console.log(`color shredder seed: ${seed}`);
+
     // initialize working canvas
-    const color_shredder_canvas = new wasm.Canvas();
+    let color_shredder_canvas = new wasm.Canvas(seed);
+    if (url_params.has('ordering')) {
+        color_shredder_canvas.set_ordering(url_params.get('ordering'));
+    }
+    if (url_params.has('metric')) {
+        color_shredder_canvas.set_metric(url_params.get('metric'));
+    }
+    if (url_params.has('score')) {
+        color_shredder_canvas.set_score(url_params.get('score'));
+    }
+    if (url_params.has('neighborhood')) {
+        color_shredder_canvas.set_neighborhood(url_params.get('neighborhood'));
+    }
+    if (url_params.has('topology')) {
+        color_shredder_canvas.set_topology(url_params.get('topology'));
+    }
+    if (url_params.has('seeds')) {
+        // e.g. ?seeds=100,100;400,300,%23ff8800
+        color_shredder_canvas.set_seed_points(url_params.get('seeds'));
+    }
+
+    // ?run=SHARE_STRING restarts with a shared run, s copies a link to this one
+    if (url_params.has('run')) {
+        try {
+            color_shredder_canvas.load_share_string(url_params.get('run'));
+        } catch (error) {
+            console.error(`cannot load the shared run: ${error}`);
+        }
+    }
+
+    // the rgba pixels of an image scaled to width x height
+    const image_rgba = (url, width, height) => new Promise((resolve, reject) => {
+        const image = new Image();
+        image.onload = () => {
+            const canvas = document.createElement('canvas');
+            canvas.width = width;
+            canvas.height = height;
+            const context = canvas.getContext('2d');
+            context.drawImage(image, 0, 0, width, height);
+            resolve(new Uint8Array(context.getImageData(0, 0, width, height).data.buffer));
+        };
+        image.onerror = () => reject(`cannot load the mask ${url}`);
+        image.src = url;
+    });
+
+    // ?preset=URL restarts with a json preset, its mask is looked up next to it
+    if (url_params.has('preset')) {
+        const preset_url = new URL(url_params.get('preset'), window.location.href);
+        fetch(preset_url)
+            .then(response => response.text())
+            .then(async json => {
+                const preset = JSON.parse(json);
+                const mask_rgba = preset.mask === undefined
+                    ? undefined
+                    : await image_rgba(new URL(preset.mask, preset_url), preset.width, preset.height);
+                color_shredder_canvas.load_config(json, mask_rgba);
+            })
+            .catch(error => console.error(error));
+    }
+
+    // click to drop a seed point, drag to seed a stroke. shift-drag paints
+    // forbidden cells and alt-drag cells that are filled last
+    const MASK_BRUSH_RADIUS = 4;
+    let stroke = null;
+    let stroke_mask = null;
+    html_canvas.style.touchAction = 'none';
+    html_canvas.addEventListener('pointerdown', event => {
+        stroke = [event.offsetX, event.offsetY];
+        stroke_mask = event.shiftKey ? 'forbidden' : event.altKey ? 'fill-last' : null;
+        html_canvas.setPointerCapture(event.pointerId);
+    });
+    html_canvas.addEventListener('pointermove', event => {
+        if (stroke !== null) {
+            stroke.push(event.offsetX, event.offsetY);
+        }
+    });
+    html_canvas.addEventListener('pointerup', () => {
+        if (stroke === null) {
+            return;
+        }
+        try {
+            if (stroke_mask !== null) {
+                const note = color_shredder_canvas.paint_mask_stroke(new Float32Array(stroke), stroke_mask, MASK_BRUSH_RADIUS);
+                if (note !== undefined) {
+                    console.warn(note);
+                }
+            } else if (stroke.length === 2) {
+                color_shredder_canvas.add_seed_point_at(stroke[0], stroke[1]);
+            } else {
+                color_shredder_canvas.add_seed_stroke(new Float32Array(stroke));
+            }
+        } catch (error) {
+            console.error(error);
+        }
+        stroke = null;
+    });
+    html_canvas.addEventListener('pointercancel', () => {
+        stroke = null;
+    });
+
+    const ANIMATION_FRAMES = 150;
+    const ANIMATION_FRAME_DELAY_MS = 40;
+    // a replay plays through the run in this many seconds
+    const REPLAY_SECONDS = 10;
+    const download = (bytes, type, extension) => {
+        const blob = new Blob([bytes], { type: type });
+        const link = document.createElement('a');
+        link.href = URL.createObjectURL(blob);
+        link.download = `color-shredder-${color_shredder_canvas.seed()}.${extension}`;
+        link.click();
+        URL.revokeObjectURL(link.href);
+    };
+
+    // k downloads a checkpoint of the run, dropping one on the canvas resumes
+    // it. p downloads the grid as a png, a the run so far as an animated png,
+    // o its placement order, c its settings as a preset and v logs whether the
+    // grid uses every color exactly once. s copies a link to the run. r
+    // toggles a replay of the run, dropping a png together with its placement
+    // order replays that run. while replaying the arrow keys play backwards
+    // and forwards and space pauses
+    window.addEventListener('keydown', event => {
+        try {
+            handle_key(event);
+        } catch (error) {
+            console.error(error);
+        }
+    });
+    const handle_key = event => {
+        if (event.key === 'k') {
+            download(color_shredder_canvas.checkpoint(), 'application/octet-stream', 'checkpoint');
+        } else if (event.key === 'p') {
+            download(color_shredder_canvas.export_png(), 'image/png', 'png');
+        } else if (event.key === 'a') {
+            const animation = color_shredder_canvas.export_animation(ANIMATION_FRAMES, ANIMATION_FRAME_DELAY_MS);
+            download(animation, 'image/png', 'animation.png');
+        } else if (event.key === 's') {
+            const link = new URL(window.location.pathname, window.location.href);
+            link.searchParams.set('run', color_shredder_canvas.share_string());
+            navigator.clipboard.writeText(link.href);
+            console.log(link.href);
+        } else if (event.key === 'c') {
+            download(color_shredder_canvas.config(), 'application/json', 'json');
+        } else if (event.key === 'o') {
+            download(color_shredder_canvas.recording(), 'application/octet-stream', 'recording');
+        } else if (event.key === 'r') {
+            if (color_shredder_canvas.is_replaying()) {
+                color_shredder_canvas.stop_replay();
+            } else {
+                color_shredder_canvas.replay_run();
+                color_shredder_canvas.set_playback_speed(color_shredder_canvas.replay_length() / REPLAY_SECONDS);
+            }
+        } else if (color_shredder_canvas.is_replaying() && ['ArrowLeft', 'ArrowRight', ' '].includes(event.key)) {
+            const speed = color_shredder_canvas.replay_length() / REPLAY_SECONDS;
+            const direction = { 'ArrowLeft': -1, 'ArrowRight': 1, ' ': 0 }[event.key];
+            color_shredder_canvas.set_playback_speed(direction * speed);
+        } else if (event.key === 'v') {
+            const validation = color_shredder_canvas.validate();
+            console.log(validation.summary());
+            validation.free();
+        }
+    };
+    // set by ?worker below
+    let worker = null;
+    html_canvas.addEventListener('dragover', event => event.preventDefault());
+    html_canvas.addEventListener('drop', async event => {
+        event.preventDefault();
+        const files = Array.from(event.dataTransfer.files);
+        const png = files.find(file => file.name.endsWith('.png'));
+        const recording = files.find(file => file.name.endsWith('.recording'));
+        const file = files[0];
+        if (file === undefined) {
+            return;
+        }
+        try {
+            if (png !== undefined && recording !== undefined) {
+                color_shredder_canvas.load_replay(
+                    new Uint8Array(await png.arrayBuffer()),
+                    new Uint8Array(await recording.arrayBuffer()),
+                );
+                color_shredder_canvas.set_playback_speed(color_shredder_canvas.replay_length() / REPLAY_SECONDS);
+                return;
+            }
+            const checkpoint = new Uint8Array(await file.arrayBuffer());
+            const resumed = wasm.Canvas.from_checkpoint(checkpoint);
+            color_shredder_canvas.free();
+            color_shredder_canvas = resumed;
+            // the resumed run continues on this thread
+            if (worker !== null) {
+                worker.terminate();
+                worker = null;
+            }
+        } catch (error) {
+            console.error(error);
+        }
+    });
+
+    // ?worker places the colors in a web worker and only draws them here, it
+    // runs the settings given by the other parameters and restarts with them
+    if (url_params.has('worker')) {
+        worker = new Worker(new URL('./worker.js', import.meta.url));
+        worker.onmessage = event => {
+            try {
+                color_shredder_canvas.apply_worker_message(new Uint8Array(event.data));
+            } catch (error) {
+                console.error(error);
+            }
+        };
+        color_shredder_canvas.use_worker(worker);
+    }
+
     const initial_time = Date.now();
 
     // render loop
@@ -44,7 +262,12 @@ color_shredder.then(wasm => {
 
             let elapsed_time = current_time - initial_time;
             // Rust Update Call
-            color_shredder_canvas.update(elapsed_time, window.innerHeight, window.innerWidth);
+            if (url_params.has('steps')) {
+                color_shredder_canvas.update(elapsed_time, window.innerHeight, window.innerWidth, Infinity, Number(url_params.get('steps')));
+            } else {
+                color_shredder_canvas.update(elapsed_time, window.innerHeight, window.innerWidth, STEP_BUDGET_MS, 0);
+            }
+            document.title = `${color_shredder_canvas.placed()} placed, ${color_shredder_canvas.remaining()} left, ${Math.round(color_shredder_canvas.placements_per_second())}/s`;
             // Rust Render Call
             color_shredder_canvas.render();
         }
@@ -27,7 +27,6 @@ pub fn update_dynamic_data(time: f32, canvas_height: f32, canvas_width: f32) {
         control_right: half_canvas_width + half_display_size,
 
         time: time,
-        ..*data.clone()
     });
 }
 
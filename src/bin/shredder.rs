@@ -0,0 +1,259 @@
+// command line tools for color shredder runs
+//
+//   shredder render --output IMAGE.png [--config PRESET.json|toml] [--width W]
+//       [--height H] [--seed SEED] [--bits N] [--metric M] [--ordering O]
+//       [--score S] [--neighborhood N] [--topology T] [--seeds "x,y;x,y,#rrggbb"]
+//       [--parallel serial|exact|batched=N]
+//   shredder validate IMAGE.png [--bits N | --sample COUNT --seed SEED | --histogram SOURCE.png]
+//
+// render runs the placement engine headlessly and writes the grid as a png
+// with the settings in text chunks. options after a preset override it. grids
+// the size of a full color cube use all of its colors, other sizes a sample
+// of the 24 bit cube. without a preset runs are scored by the average
+// neighbor unless --score says otherwise, as only that score is served by the
+// frontier index and large grids take far too long with a linear scan of the
+// frontier. builds with the parallel feature can spread the search over all
+// cores, exact gives the same image as a serial run.
+//
+// validate checks that an image uses every color of its source exactly once.
+// without a source option it checks against the run settings render wrote
+// into the image, or else the image has to be a full color cube, whose bits
+// follow from its size. exits with 1 when the image is invalid
+
+use color_shredder::engine::{
+    decode_png, png_run_settings, validate_png, ColorSource, Engine, Mask, PlacementScore,
+    ProgressMeter, RunConfig, Settings,
+};
+use std::io::Write;
+use std::path::Path;
+use std::process::exit;
+use std::time::Instant;
+
+const USAGE: &str = "usage: shredder render --output IMAGE.png [--config PRESET.json|toml] \
+    [--width W] [--height H] [--seed SEED] [--bits N] [--metric M] [--ordering O] \
+    [--score S] [--neighborhood N] [--topology T] [--seeds \"x,y;x,y,#rrggbb\"] \
+    [--parallel serial|exact|batched=N]
+       shredder validate IMAGE.png \
+    [--bits N | --sample COUNT --seed SEED | --histogram SOURCE.png]";
+
+// the progress line is redrawn this often
+const PROGRESS_INTERVAL_MS: f64 = 250.0;
+const PROGRESS_WINDOW_MS: f64 = 2000.0;
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("render") => render(&args[1..]).map(|_| true),
+        Some("validate") => validate(&args[1..]),
+        _ => Err(String::from(USAGE)),
+    };
+    match result {
+        Ok(true) => {}
+        Ok(false) => exit(1),
+        Err(error) => {
+            eprintln!("{}", error);
+            exit(2);
+        }
+    }
+}
+
+fn read(path: &str) -> Result<Vec<u8>, String> {
+    std::fs::read(path).map_err(|error| format!("cannot read {}: {}", path, error))
+}
+
+fn parse<T: std::str::FromStr>(option: &str, value: Option<&String>) -> Result<T, String> {
+    value
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| format!("{} needs a number", option))
+}
+
+// option values with their own names, e.g. metrics, report what they expect
+fn parse_named<T: std::str::FromStr<Err = String>>(
+    option: &str,
+    value: Option<&String>,
+) -> Result<T, String> {
+    value
+        .ok_or_else(|| format!("{} needs a value", option))?
+        .parse()
+}
+
+fn render(args: &[String]) -> Result<(), String> {
+    let mut settings = Settings {
+        score: PlacementScore::AverageNeighbor,
+        ..Settings::new(512, 512, 0)
+    };
+    // filled in to match the grid unless given
+    let mut source = None;
+    let mut output = None;
+    let mut parallelism = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--output" | "-o" => output = Some(args.next().ok_or("--output needs a path")?),
+            "--config" => {
+                let path = args.next().ok_or("--config needs a path")?;
+                let (config, mask) = load_config(path)?;
+                settings = config
+                    .to_settings(mask)
+                    .map_err(|error| at_path(path, error))?;
+                source = config.source.map(|_| settings.source.clone());
+            }
+            "--width" => settings.width = parse(arg, args.next())?,
+            "--height" => settings.height = parse(arg, args.next())?,
+            "--seed" => settings.seed = parse(arg, args.next())?,
+            "--bits" => {
+                source = Some(ColorSource::Cube {
+                    bits: parse(arg, args.next())?,
+                })
+            }
+            "--metric" => settings.metric = parse_named(arg, args.next())?,
+            "--ordering" => settings.ordering = parse_named(arg, args.next())?,
+            "--score" => settings.score = parse_named(arg, args.next())?,
+            "--neighborhood" => settings.neighborhood = parse_named(arg, args.next())?,
+            "--topology" => settings.topology = parse_named(arg, args.next())?,
+            "--seeds" => {
+                let spec: &String = args.next().ok_or("--seeds needs a list of points")?;
+                settings.seed_points = spec
+                    .split(';')
+                    .filter(|point| !point.trim().is_empty())
+                    .map(str::parse)
+                    .collect::<Result<_, String>>()?;
+            }
+            "--parallel" => parallelism = Some(args.next().ok_or("--parallel needs a mode")?),
+            _ => return Err(format!("unexpected argument '{}'\n{}", arg, USAGE)),
+        }
+    }
+    let output = output.ok_or(USAGE)?;
+    settings.source = source.unwrap_or_else(|| ColorSource::filling(settings.placeable_cells()));
+
+    let mut engine = Engine::new(settings)?;
+    if let Some(parallelism) = parallelism {
+        set_parallelism(&mut engine, parallelism)?;
+    }
+    let total = engine.remaining();
+    let start = Instant::now();
+    let now_ms = || start.elapsed().as_secs_f64() * 1000.0;
+    let mut progress = ProgressMeter::new(PROGRESS_WINDOW_MS);
+    let mut stderr = std::io::stderr();
+    while !engine.is_finished() {
+        engine.step_within(PROGRESS_INTERVAL_MS, 0, now_ms);
+        progress.record(now_ms(), engine.placed());
+        let _ = write!(
+            stderr,
+            "\rplaced {} of {} colors ({:.1}%), {:.0} colors/s   ",
+            engine.placed(),
+            total,
+            engine.placed() as f64 * 100.0 / total.max(1) as f64,
+            progress.placements_per_second()
+        );
+    }
+    let _ = writeln!(
+        stderr,
+        "\rplaced {} colors in {:.1}s{:30}",
+        total,
+        now_ms() / 1000.0,
+        ""
+    );
+
+    let png = engine.encode_png(true)?;
+    std::fs::write(output, png).map_err(|error| format!("cannot write {}: {}", output, error))
+}
+
+#[cfg(feature = "parallel")]
+fn set_parallelism(engine: &mut Engine, parallelism: &str) -> Result<(), String> {
+    engine.set_parallelism(parallelism.parse()?)
+}
+
+#[cfg(not(feature = "parallel"))]
+fn set_parallelism(_engine: &mut Engine, _parallelism: &str) -> Result<(), String> {
+    Err(String::from(
+        "--parallel needs a build with the parallel feature, e.g. cargo build --features parallel",
+    ))
+}
+
+fn at_path(path: &str, error: String) -> String {
+    format!("{}: {}", path, error)
+}
+
+// a json or toml preset and the mask it refers to, which is looked up next to
+// the preset
+fn load_config(path: &str) -> Result<(RunConfig, Option<Mask>), String> {
+    let bytes = read(path)?;
+    let text = String::from_utf8(bytes).map_err(|_| format!("{} is not utf-8 text", path))?;
+    let config = if path.ends_with(".toml") {
+        RunConfig::from_toml(&text)
+    } else {
+        RunConfig::from_json(&text)
+    }
+    .map_err(|error| at_path(path, error))?;
+
+    let mask = match &config.mask {
+        Some(reference) => {
+            let mask_path = Path::new(path).with_file_name(reference);
+            let mask_path = mask_path.to_string_lossy();
+            let (width, height, rgba) = decode_png(&read(&mask_path)?)?;
+            if (width, height) != (config.width, config.height) {
+                return Err(format!(
+                    "the mask {} is {}x{} but the grid is {}x{}",
+                    mask_path, width, height, config.width, config.height
+                ));
+            }
+            Some(Mask::from_alpha(width, height, &rgba)?)
+        }
+        None => None,
+    };
+    Ok((config, mask))
+}
+
+// prints the report, returns whether the image is valid
+fn validate(args: &[String]) -> Result<bool, String> {
+    let mut image = None;
+    let mut source = None;
+    let mut seed = 0;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--bits" => {
+                source = Some(ColorSource::Cube {
+                    bits: parse(arg, args.next())?,
+                })
+            }
+            "--sample" => {
+                source = Some(ColorSource::SampledCube {
+                    count: parse(arg, args.next())?,
+                })
+            }
+            "--seed" => seed = parse(arg, args.next())?,
+            "--histogram" => {
+                let path = args.next().ok_or("--histogram needs a png")?;
+                let (_, _, rgba) = decode_png(&read(path)?)?;
+                source = Some(ColorSource::from_rgba(&rgba)?);
+            }
+            _ if image.is_none() && !arg.starts_with("--") => image = Some(arg),
+            _ => return Err(format!("unexpected argument '{}'\n{}", arg, USAGE)),
+        }
+    }
+    let image = image.ok_or(USAGE)?;
+    let png = read(image)?;
+    let (width, height, _) = decode_png(&png)?;
+
+    let settings = match (source, png_run_settings(&png)?) {
+        (Some(source), _) => Settings {
+            source,
+            ..Settings::new(width, height, seed)
+        },
+        (None, Some(settings)) => settings,
+        (None, None) => Settings {
+            source: ColorSource::cube_for(width * height).ok_or_else(|| {
+                format!(
+                    "{}x{} is not the size of a full color cube, pass the source",
+                    width, height
+                )
+            })?,
+            ..Settings::new(width, height, seed)
+        },
+    };
+    let report = validate_png(&settings, &png)?;
+    println!("{}: {}", image, report);
+    Ok(report.is_valid())
+}
@@ -0,0 +1,122 @@
+use super::{Engine, Recording};
+use std::convert::TryFrom;
+
+// how a recorded run is cut into animation frames
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AnimationSettings {
+    pub placements_per_frame: usize,
+    pub frame_delay_ms: u16,
+    // how long the finished image stays up before the animation loops
+    pub final_delay_ms: u16,
+}
+
+impl AnimationSettings {
+    // spreads `placements` over at most `frames` frames
+    pub fn with_frame_count(placements: usize, frames: usize, frame_delay_ms: u16) -> Self {
+        Self {
+            placements_per_frame: placements.div_ceil(frames.max(1)).max(1),
+            frame_delay_ms,
+            final_delay_ms: frame_delay_ms,
+        }
+    }
+
+    pub fn frame_count(&self, placements: usize) -> usize {
+        placements.div_ceil(self.placements_per_frame).max(1)
+    }
+}
+
+impl Engine {
+    // the recorded run as a looping animated png, see `encode_apng`
+    pub fn encode_animation(&self, settings: &AnimationSettings) -> Result<Vec<u8>, String> {
+        let recording = self
+            .recording()
+            .ok_or("the run was not recorded, start recording before the first step")?;
+        encode_apng(recording, &self.pixels, settings)
+    }
+}
+
+// an animated png showing the grid after every `placements_per_frame`
+// placements of `recording`, colors come from the rgba `grid`. each frame
+// after the first only covers the cells placed since the previous one
+pub fn encode_apng(
+    recording: &Recording,
+    grid: &[u8],
+    settings: &AnimationSettings,
+) -> Result<Vec<u8>, String> {
+    let (width, height) = (recording.width(), recording.height());
+    if grid.len() != width * height * 4 {
+        return Err(format!(
+            "grid of {} bytes does not match the {}x{} recording",
+            grid.len(),
+            width,
+            height
+        ));
+    }
+    if settings.placements_per_frame == 0 {
+        return Err(String::from(
+            "an animation needs at least one placement per frame",
+        ));
+    }
+    let frame_count = settings.frame_count(recording.len());
+    let frame_count_u32 = u32::try_from(frame_count)
+        .map_err(|_| format!("{} frames are too many for a png", frame_count))?;
+
+    let mut bytes = Vec::new();
+    let mut encoder = png::Encoder::new(&mut bytes, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .set_animated(frame_count_u32, 0)
+        .map_err(|error| error.to_string())?;
+    let mut writer = encoder.write_header().map_err(|error| error.to_string())?;
+
+    // an empty recording still gets its one frame
+    let mut frame = vec![0; grid.len()];
+    for (index, cells) in recording
+        .cells()
+        .chunks(settings.placements_per_frame)
+        .chain(std::iter::once(&[][..]))
+        .take(frame_count)
+        .enumerate()
+    {
+        let (mut left, mut top, mut right, mut bottom) = (width, height, 0, 0);
+        for &cell in cells {
+            let cell = cell as usize;
+            frame[cell * 4..cell * 4 + 4].copy_from_slice(&grid[cell * 4..cell * 4 + 4]);
+            let (x, y) = (cell % width, cell / width);
+            left = left.min(x);
+            top = top.min(y);
+            right = right.max(x + 1);
+            bottom = bottom.max(y + 1);
+        }
+        // the first frame is the default image and spans the grid, so does
+        // a frame without placements
+        if index == 0 || cells.is_empty() {
+            left = 0;
+            top = 0;
+            right = width;
+            bottom = height;
+        }
+
+        let delay = if index + 1 == frame_count {
+            settings.final_delay_ms
+        } else {
+            settings.frame_delay_ms
+        };
+        let mut region = Vec::with_capacity((right - left) * (bottom - top) * 4);
+        for y in top..bottom {
+            region.extend_from_slice(&frame[(y * width + left) * 4..(y * width + right) * 4]);
+        }
+        let result = writer
+            .set_frame_delay(delay, 1000)
+            .and_then(|_| writer.set_blend_op(png::BlendOp::Source))
+            .and_then(|_| writer.set_dispose_op(png::DisposeOp::None))
+            .and_then(|_| writer.reset_frame_position())
+            .and_then(|_| writer.set_frame_dimension((right - left) as u32, (bottom - top) as u32))
+            .and_then(|_| writer.set_frame_position(left as u32, top as u32))
+            .and_then(|_| writer.write_image_data(&region));
+        result.map_err(|error| error.to_string())?;
+    }
+    writer.finish().map_err(|error| error.to_string())?;
+    Ok(bytes)
+}
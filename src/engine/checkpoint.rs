@@ -0,0 +1,191 @@
+use super::{check_settings, indexable, read_settings, write_settings, ByteReader, ByteWriter};
+use super::{CellMask, Engine, Frontier, Recording};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+const CHECKPOINT_MAGIC: &[u8; 4] = b"CSCP";
+// version 2 added the placement recording
+pub const CHECKPOINT_VERSION: u16 = 2;
+
+impl Engine {
+    // the full state of the run as a versioned binary blob. layout after the
+    // magic and version: settings, rng, colors in placement order, the next
+    // color, a bitmap of filled cells with their colors, frontier and deferred
+    // fill last cells, the recorded placement order if any
+    pub fn checkpoint(&self) -> Vec<u8> {
+        let mut writer = ByteWriter::new();
+        writer.bytes(CHECKPOINT_MAGIC);
+        writer.u16(CHECKPOINT_VERSION);
+        write_settings(&mut writer, &self.settings);
+
+        writer.bytes(&self.rng.get_seed());
+        writer.u64(self.rng.get_stream());
+        writer.u128(self.rng.get_word_pos());
+
+        writer.usize(self.colors.len());
+        for &color in &self.colors {
+            writer.rgb(color);
+        }
+        writer.usize(self.next_color);
+
+        let mut filled = vec![0u8; self.cell_count().div_ceil(8)];
+        for cell in (0..self.cell_count()).filter(|&cell| self.is_filled(cell)) {
+            filled[cell / 8] |= 1 << (cell % 8);
+        }
+        writer.bytes(&filled);
+        for cell in 0..self.cell_count() {
+            if let Some(color) = self.cell_color(cell) {
+                writer.rgb(color);
+            }
+        }
+
+        for cells in [&self.frontier, &self.deferred].iter() {
+            writer.usize(cells.len());
+            for cell in cells.iter() {
+                writer.u32(cell as u32);
+            }
+        }
+
+        match &self.recording {
+            Some(recording) => {
+                writer.u8(1);
+                writer.usize(recording.len());
+                for &cell in recording.cells() {
+                    writer.u32(cell);
+                }
+            }
+            None => writer.u8(0),
+        }
+        writer.into_bytes()
+    }
+
+    // continues a run from `checkpoint`, stepping it to the end gives the
+    // same image as a run that was never interrupted
+    pub fn from_checkpoint(checkpoint: &[u8]) -> Result<Self, String> {
+        let mut reader = ByteReader::new(checkpoint);
+        if reader.bytes(4).ok() != Some(&CHECKPOINT_MAGIC[..]) {
+            return Err(String::from("not a color shredder checkpoint"));
+        }
+        let version = reader.u16()?;
+        if !(1..=CHECKPOINT_VERSION).contains(&version) {
+            return Err(format!(
+                "checkpoint version {} is not supported, expected {}",
+                version, CHECKPOINT_VERSION
+            ));
+        }
+        let settings = read_settings(&mut reader)?;
+        check_settings(&settings)?;
+
+        let mut seed = [0; 32];
+        seed.copy_from_slice(reader.bytes(32)?);
+        let mut rng = ChaCha8Rng::from_seed(seed);
+        rng.set_stream(reader.u64()?);
+        rng.set_word_pos(reader.u128()?);
+
+        let color_count = reader.length(3)?;
+        if color_count != settings.placeable_cells() {
+            return Err(format!(
+                "checkpoint holds {} colors for {} cells",
+                color_count,
+                settings.placeable_cells()
+            ));
+        }
+        let colors = (0..color_count)
+            .map(|_| reader.rgb())
+            .collect::<Result<Vec<_>, _>>()?;
+        let next_color = reader.usize()?;
+        if next_color > color_count {
+            return Err(format!(
+                "checkpoint placed {} of {} colors",
+                next_color, color_count
+            ));
+        }
+
+        let mut engine = Self::with_colors(settings, rng, colors);
+        let cell_count = engine.cell_count();
+        let filled = reader.bytes(cell_count.div_ceil(8))?;
+        for cell in (0..cell_count).filter(|&cell| filled[cell / 8] & (1 << (cell % 8)) != 0) {
+            if engine.cell_mask(cell) == CellMask::Forbidden {
+                return Err(format!("checkpoint fills the forbidden cell {}", cell));
+            }
+            let color = reader.rgb()?;
+            engine.pixels[cell * 4..cell * 4 + 4]
+                .copy_from_slice(&[color[0], color[1], color[2], 255]);
+            engine.placed += 1;
+            match engine.cell_mask(cell) {
+                CellMask::Allowed => engine.allowed_left -= 1,
+                _ => engine.fill_last_left -= 1,
+            }
+        }
+        if engine.placed != next_color {
+            return Err(format!(
+                "checkpoint fills {} cells but placed {} colors",
+                engine.placed, next_color
+            ));
+        }
+        engine.next_color = next_color;
+
+        engine.frontier = read_cells(&mut reader, &engine)?;
+        engine.deferred = read_cells(&mut reader, &engine)?;
+        if version >= 2 {
+            engine.recording = read_recording(&mut reader, &engine)?;
+        }
+        if !reader.is_empty() {
+            return Err(String::from("checkpoint has trailing data"));
+        }
+
+        // keys are recomputed from the grid exactly like during the run
+        engine.index = None;
+        engine.set_frontier_index(indexable(&engine.settings));
+        engine.dirty.mark_all();
+        Ok(engine)
+    }
+}
+
+fn read_cells(reader: &mut ByteReader, engine: &Engine) -> Result<Frontier, String> {
+    let mut cells = Frontier::new(engine.cell_count());
+    for _ in 0..reader.length(4)? {
+        let cell = reader.u32()? as usize;
+        if cell >= engine.cell_count() || engine.is_filled(cell) {
+            return Err(format!("checkpoint lists cell {} as open", cell));
+        }
+        if engine.cell_mask(cell) == CellMask::Forbidden {
+            return Err(format!(
+                "checkpoint lists the forbidden cell {} as open",
+                cell
+            ));
+        }
+        if !cells.insert(cell) {
+            return Err(format!("checkpoint lists cell {} twice", cell));
+        }
+    }
+    Ok(cells)
+}
+
+fn read_recording(reader: &mut ByteReader, engine: &Engine) -> Result<Option<Recording>, String> {
+    match reader.u8()? {
+        0 => return Ok(None),
+        1 => {}
+        tag => return Err(format!("unknown recording tag {}", tag)),
+    }
+    let length = reader.length(4)?;
+    if length != engine.placed {
+        return Err(format!(
+            "checkpoint records {} of {} placements",
+            length, engine.placed
+        ));
+    }
+    let mut recording = Recording::new(engine.width, engine.height);
+    let mut recorded = Frontier::new(engine.cell_count());
+    for _ in 0..length {
+        let cell = reader.u32()? as usize;
+        if cell >= engine.cell_count() || !engine.is_filled(cell) || !recorded.insert(cell) {
+            return Err(format!(
+                "checkpoint records an invalid placement of cell {}",
+                cell
+            ));
+        }
+        recording.push(cell);
+    }
+    Ok(Some(recording))
+}
@@ -0,0 +1,442 @@
+use super::{
+    CellMask, ColorOrdering, ColorSource, Mask, Metric, Neighborhood, PlacementScore, Rgb,
+    SeedPoint, Settings, Topology,
+};
+use std::convert::TryFrom;
+
+// little endian binary encoding shared by checkpoints and share strings
+
+#[derive(Default)]
+pub struct ByteWriter {
+    bytes: Vec<u8>,
+}
+
+impl ByteWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    pub fn bytes(&mut self, bytes: &[u8]) {
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    pub fn u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    pub fn u16(&mut self, value: u16) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    pub fn u32(&mut self, value: u32) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    pub fn u64(&mut self, value: u64) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    pub fn u128(&mut self, value: u128) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    pub fn i64(&mut self, value: i64) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    pub fn f64(&mut self, value: f64) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    // sizes are always stored as 64 bits so wasm32 and native agree
+    pub fn usize(&mut self, value: usize) {
+        self.u64(value as u64);
+    }
+
+    pub fn rgb(&mut self, color: Rgb) {
+        self.bytes(&color);
+    }
+
+    // unsigned leb128, values below 128 take a single byte
+    pub fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.u8(value as u8 | 0x80);
+            value >>= 7;
+        }
+        self.u8(value as u8);
+    }
+}
+
+pub struct ByteReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.position == self.bytes.len()
+    }
+
+    pub fn bytes(&mut self, count: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .position
+            .checked_add(count)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| {
+                format!(
+                    "data ends after {} bytes, needed {} more at offset {}",
+                    self.bytes.len(),
+                    count,
+                    self.position
+                )
+            })?;
+        let bytes = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.bytes(N)?);
+        Ok(array)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub fn u16(&mut self) -> Result<u16, String> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    pub fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    pub fn u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    pub fn u128(&mut self) -> Result<u128, String> {
+        Ok(u128::from_le_bytes(self.array()?))
+    }
+
+    pub fn i64(&mut self) -> Result<i64, String> {
+        Ok(i64::from_le_bytes(self.array()?))
+    }
+
+    pub fn f64(&mut self) -> Result<f64, String> {
+        Ok(f64::from_le_bytes(self.array()?))
+    }
+
+    pub fn usize(&mut self) -> Result<usize, String> {
+        let value = self.u64()?;
+        usize::try_from(value).map_err(|_| format!("size {} does not fit this platform", value))
+    }
+
+    // a length prefix for items of at least `item_size` bytes, checked against
+    // the bytes left so corrupt data cannot request huge allocations
+    pub fn length(&mut self, item_size: usize) -> Result<usize, String> {
+        let length = self.usize()?;
+        let left = self.bytes.len() - self.position;
+        if length.saturating_mul(item_size) > left {
+            return Err(format!(
+                "length {} at offset {} exceeds the remaining {} bytes",
+                length, self.position, left
+            ));
+        }
+        Ok(length)
+    }
+
+    pub fn rgb(&mut self) -> Result<Rgb, String> {
+        self.array()
+    }
+
+    pub fn varint(&mut self) -> Result<u64, String> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.u8()?;
+            let bits = u64::from(byte & 0x7f);
+            if bits << shift >> shift != bits {
+                break;
+            }
+            value |= bits << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(format!(
+            "varint ending at offset {} does not fit 64 bits",
+            self.position
+        ))
+    }
+}
+
+fn unknown_tag(what: &str, tag: u8) -> String {
+    format!("unknown {} tag {}", what, tag)
+}
+
+pub fn write_settings(writer: &mut ByteWriter, settings: &Settings) {
+    writer.usize(settings.width);
+    writer.usize(settings.height);
+    writer.u64(settings.seed);
+
+    match &settings.source {
+        ColorSource::Cube { bits } => {
+            writer.u8(0);
+            writer.u32(*bits);
+        }
+        ColorSource::SampledCube { count } => {
+            writer.u8(1);
+            writer.usize(*count);
+        }
+        ColorSource::Palette(colors) => {
+            writer.u8(2);
+            writer.usize(colors.len());
+            for &color in colors {
+                writer.rgb(color);
+            }
+        }
+        ColorSource::Histogram(counts) => {
+            writer.u8(3);
+            writer.usize(counts.len());
+            for &(color, count) in counts {
+                writer.rgb(color);
+                writer.usize(count);
+            }
+        }
+    }
+
+    match settings.ordering {
+        ColorOrdering::Shuffle => writer.u8(0),
+        ColorOrdering::Hue => writer.u8(1),
+        ColorOrdering::Luminance => writer.u8(2),
+        ColorOrdering::Saturation => writer.u8(3),
+        ColorOrdering::Hilbert => writer.u8(4),
+        ColorOrdering::Morton => writer.u8(5),
+        ColorOrdering::RandomHueBias { strength } => {
+            writer.u8(6);
+            writer.f64(strength);
+        }
+    }
+
+    writer.u8(match settings.metric {
+        Metric::Rgb => 0,
+        Metric::LumaWeighted => 1,
+        Metric::Redmean => 2,
+        Metric::Cie76 => 3,
+        Metric::Ciede2000 => 4,
+        Metric::Oklab => 5,
+    });
+    writer.u8(match settings.score {
+        PlacementScore::MinNeighbor => 0,
+        PlacementScore::AverageNeighbor => 1,
+        PlacementScore::WeightedNeighbor => 2,
+    });
+
+    match &settings.neighborhood {
+        Neighborhood::VonNeumann => writer.u8(0),
+        Neighborhood::Moore => writer.u8(1),
+        Neighborhood::Disk { radius } => {
+            writer.u8(2);
+            writer.u32(*radius);
+        }
+        Neighborhood::Hexagonal => writer.u8(3),
+        Neighborhood::Custom(offsets) => {
+            writer.u8(4);
+            writer.usize(offsets.len());
+            for &(dx, dy, weight) in offsets {
+                writer.i64(dx as i64);
+                writer.i64(dy as i64);
+                writer.f64(weight);
+            }
+        }
+    }
+
+    writer.u8(match settings.topology {
+        Topology::Bounded => 0,
+        Topology::WrapHorizontal => 1,
+        Topology::WrapVertical => 2,
+        Topology::Torus => 3,
+        Topology::Mirrored => 4,
+    });
+
+    writer.usize(settings.seed_points.len());
+    for point in &settings.seed_points {
+        writer.usize(point.x);
+        writer.usize(point.y);
+        match point.color {
+            Some(color) => {
+                writer.u8(1);
+                writer.rgb(color);
+            }
+            None => writer.u8(0),
+        }
+    }
+
+    match &settings.mask {
+        Some(mask) => {
+            writer.u8(1);
+            writer.usize(mask.width());
+            writer.usize(mask.height());
+            for cell in 0..mask.width() * mask.height() {
+                writer.u8(match mask.cell(cell) {
+                    CellMask::Forbidden => 0,
+                    CellMask::Allowed => 1,
+                    CellMask::FillLast => 2,
+                });
+            }
+        }
+        None => writer.u8(0),
+    }
+}
+
+pub fn read_settings(reader: &mut ByteReader) -> Result<Settings, String> {
+    let width = reader.usize()?;
+    let height = reader.usize()?;
+    let seed = reader.u64()?;
+
+    let source = match reader.u8()? {
+        0 => ColorSource::Cube {
+            bits: reader.u32()?,
+        },
+        1 => ColorSource::SampledCube {
+            count: reader.usize()?,
+        },
+        2 => {
+            let length = reader.length(3)?;
+            ColorSource::Palette(
+                (0..length)
+                    .map(|_| reader.rgb())
+                    .collect::<Result<_, _>>()?,
+            )
+        }
+        3 => {
+            let length = reader.length(11)?;
+            ColorSource::Histogram(
+                (0..length)
+                    .map(|_| Ok((reader.rgb()?, reader.usize()?)))
+                    .collect::<Result<_, String>>()?,
+            )
+        }
+        tag => return Err(unknown_tag("color source", tag)),
+    };
+
+    let ordering = match reader.u8()? {
+        0 => ColorOrdering::Shuffle,
+        1 => ColorOrdering::Hue,
+        2 => ColorOrdering::Luminance,
+        3 => ColorOrdering::Saturation,
+        4 => ColorOrdering::Hilbert,
+        5 => ColorOrdering::Morton,
+        6 => ColorOrdering::RandomHueBias {
+            strength: reader.f64()?,
+        },
+        tag => return Err(unknown_tag("color ordering", tag)),
+    };
+
+    let metric = match reader.u8()? {
+        0 => Metric::Rgb,
+        1 => Metric::LumaWeighted,
+        2 => Metric::Redmean,
+        3 => Metric::Cie76,
+        4 => Metric::Ciede2000,
+        5 => Metric::Oklab,
+        tag => return Err(unknown_tag("color metric", tag)),
+    };
+    let score = match reader.u8()? {
+        0 => PlacementScore::MinNeighbor,
+        1 => PlacementScore::AverageNeighbor,
+        2 => PlacementScore::WeightedNeighbor,
+        tag => return Err(unknown_tag("placement score", tag)),
+    };
+
+    let neighborhood = match reader.u8()? {
+        0 => Neighborhood::VonNeumann,
+        1 => Neighborhood::Moore,
+        2 => Neighborhood::Disk {
+            radius: reader.u32()?,
+        },
+        3 => Neighborhood::Hexagonal,
+        4 => {
+            let length = reader.length(24)?;
+            Neighborhood::Custom(
+                (0..length)
+                    .map(|_| {
+                        Ok((
+                            reader.i64()? as isize,
+                            reader.i64()? as isize,
+                            reader.f64()?,
+                        ))
+                    })
+                    .collect::<Result<_, String>>()?,
+            )
+        }
+        tag => return Err(unknown_tag("neighborhood", tag)),
+    };
+
+    let topology = match reader.u8()? {
+        0 => Topology::Bounded,
+        1 => Topology::WrapHorizontal,
+        2 => Topology::WrapVertical,
+        3 => Topology::Torus,
+        4 => Topology::Mirrored,
+        tag => return Err(unknown_tag("topology", tag)),
+    };
+
+    let length = reader.length(17)?;
+    let mut seed_points = Vec::with_capacity(length);
+    for _ in 0..length {
+        let (x, y) = (reader.usize()?, reader.usize()?);
+        let color = match reader.u8()? {
+            0 => None,
+            1 => Some(reader.rgb()?),
+            tag => return Err(unknown_tag("seed color", tag)),
+        };
+        seed_points.push(SeedPoint { x, y, color });
+    }
+
+    let mask = match reader.u8()? {
+        0 => None,
+        1 => {
+            let (mask_width, mask_height) = (reader.usize()?, reader.usize()?);
+            let cell_count = mask_width
+                .checked_mul(mask_height)
+                .ok_or_else(|| format!("mask of {}x{} is too large", mask_width, mask_height))?;
+            let weights = reader
+                .bytes(cell_count)?
+                .iter()
+                .map(|&cell| match cell {
+                    0 => Ok(0),
+                    1 => Ok(255),
+                    2 => Ok(128),
+                    tag => Err(unknown_tag("cell mask", tag)),
+                })
+                .collect::<Result<Vec<u8>, String>>()?;
+            Some(Mask::from_weights(mask_width, mask_height, &weights)?)
+        }
+        tag => return Err(unknown_tag("mask", tag)),
+    };
+
+    Ok(Settings {
+        width,
+        height,
+        seed,
+        source,
+        ordering,
+        metric,
+        score,
+        neighborhood,
+        topology,
+        seed_points,
+        mask,
+    })
+}
@@ -0,0 +1,269 @@
+use super::{
+    check_seed_points, parse_hex_color, ColorOrdering, ColorSource, Mask, Metric, Neighborhood,
+    PlacementScore, Rgb, SeedPoint, Settings, Topology,
+};
+use serde::de::{self, Deserializer};
+use serde::{Deserialize, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+// newest preset layout, older versions are still read
+pub const CONFIG_VERSION: u32 = 1;
+
+// every parameter of a run as a json or toml document, e.g. a saved preset.
+// options use the same names as the url parameters and missing ones fall back
+// to the engine defaults
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RunConfig {
+    pub version: u32,
+    pub width: usize,
+    pub height: usize,
+    #[serde(default)]
+    pub seed: u64,
+    // every cube color when the placeable cells fit a full cube, otherwise a
+    // sample of the 24 bit cube
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<SourceConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ordering: Option<ColorOrdering>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metric: Option<Metric>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<PlacementScore>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub neighborhood: Option<Neighborhood>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub topology: Option<Topology>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub seeds: Vec<SeedPoint>,
+    // path or url of an image whose alpha channel is the mask, the caller
+    // loads it and passes the mask to `to_settings`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mask: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub enum SourceConfig {
+    Cube { bits: u32 },
+    Sample { count: usize },
+    Palette(Vec<HexColor>),
+    Histogram(Vec<(HexColor, usize)>),
+}
+
+// a color written as "#rrggbb"
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HexColor(pub Rgb);
+
+impl fmt::Display for HexColor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let [r, g, b] = self.0;
+        write!(f, "#{:02x}{:02x}{:02x}", r, g, b)
+    }
+}
+
+impl FromStr for HexColor {
+    type Err = String;
+
+    fn from_str(color: &str) -> Result<Self, String> {
+        parse_hex_color(color)
+            .map(HexColor)
+            .ok_or_else(|| format!("invalid color '{}', expected #rrggbb", color))
+    }
+}
+
+// option values are stored under the names their Display and FromStr use
+macro_rules! serde_by_name {
+    ($($name:ty),*) => {$(
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                String::deserialize(deserializer)?
+                    .parse()
+                    .map_err(de::Error::custom)
+            }
+        }
+    )*};
+}
+
+serde_by_name!(
+    ColorOrdering,
+    Metric,
+    PlacementScore,
+    Neighborhood,
+    Topology,
+    SeedPoint,
+    HexColor
+);
+
+// read ahead of the rest so newer presets fail on their version rather than
+// on fields this release does not know
+#[derive(Deserialize)]
+struct Versioned {
+    version: u32,
+}
+
+impl RunConfig {
+    pub fn from_settings(settings: &Settings) -> Self {
+        let source = match &settings.source {
+            ColorSource::Cube { bits } => SourceConfig::Cube { bits: *bits },
+            ColorSource::SampledCube { count } => SourceConfig::Sample { count: *count },
+            ColorSource::Palette(colors) => {
+                SourceConfig::Palette(colors.iter().map(|&color| HexColor(color)).collect())
+            }
+            ColorSource::Histogram(counts) => SourceConfig::Histogram(
+                counts
+                    .iter()
+                    .map(|&(color, count)| (HexColor(color), count))
+                    .collect(),
+            ),
+        };
+        Self {
+            version: CONFIG_VERSION,
+            width: settings.width,
+            height: settings.height,
+            seed: settings.seed,
+            source: Some(source),
+            ordering: Some(settings.ordering),
+            metric: Some(settings.metric),
+            score: Some(settings.score),
+            neighborhood: Some(settings.neighborhood.clone()),
+            topology: Some(settings.topology),
+            seeds: settings.seed_points.clone(),
+            mask: None,
+        }
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        let versioned: Versioned = with_path(&mut serde_json::Deserializer::from_str(text))?;
+        check_version(versioned.version)?;
+        let config: Self = with_path(&mut serde_json::Deserializer::from_str(text))?;
+        config.check()?;
+        Ok(config)
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self, String> {
+        let versioned: Versioned = with_path(toml::Deserializer::new(text))?;
+        check_version(versioned.version)?;
+        let config: Self = with_path(toml::Deserializer::new(text))?;
+        config.check()?;
+        Ok(config)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string_pretty(self).unwrap()
+    }
+
+    // the run this preset describes, `mask` is the image the mask field
+    // refers to
+    pub fn to_settings(&self, mask: Option<Mask>) -> Result<Settings, String> {
+        if let (Some(reference), None) = (&self.mask, &mask) {
+            return Err(format!(
+                "mask: the preset refers to '{}' but no mask was loaded",
+                reference
+            ));
+        }
+        let defaults = Settings::new(self.width, self.height, self.seed);
+        let mut settings = Settings {
+            ordering: self.ordering.unwrap_or(defaults.ordering),
+            metric: self.metric.unwrap_or(defaults.metric),
+            score: self.score.unwrap_or(defaults.score),
+            neighborhood: self.neighborhood.clone().unwrap_or(defaults.neighborhood),
+            topology: self.topology.unwrap_or(defaults.topology),
+            seed_points: self.seeds.clone(),
+            mask,
+            ..defaults
+        };
+        settings.source = match &self.source {
+            Some(source) => source.to_source(),
+            None => ColorSource::filling(settings.placeable_cells()),
+        };
+        Ok(settings)
+    }
+
+    // ranges of single fields, the engine checks how they fit together
+    fn check(&self) -> Result<(), String> {
+        if self.width == 0 {
+            return Err(String::from("width: must be at least 1"));
+        }
+        if self.height == 0 {
+            return Err(String::from("height: must be at least 1"));
+        }
+        if let Some(source) = &self.source {
+            // checked against its own count only the ranges can fail
+            let source = source.to_source();
+            let count = source.count().map_err(at("source"))?;
+            source.check(count).map_err(at("source"))?;
+        }
+        if let Some(ordering) = &self.ordering {
+            ordering.check().map_err(at("ordering"))?;
+        }
+        if let Some(neighborhood) = &self.neighborhood {
+            neighborhood.check().map_err(at("neighborhood"))?;
+        }
+        check_seed_points(&self.seeds, self.width, self.height).map_err(at("seeds"))
+    }
+}
+
+impl SourceConfig {
+    fn to_source(&self) -> ColorSource {
+        match self {
+            SourceConfig::Cube { bits } => ColorSource::Cube { bits: *bits },
+            SourceConfig::Sample { count } => ColorSource::SampledCube { count: *count },
+            SourceConfig::Palette(colors) => {
+                ColorSource::Palette(colors.iter().map(|color| color.0).collect())
+            }
+            SourceConfig::Histogram(counts) => ColorSource::Histogram(
+                counts
+                    .iter()
+                    .map(|&(color, count)| (color.0, count))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+// prefixes errors of a field with its path
+fn at(path: &'static str) -> impl Fn(String) -> String {
+    move |error| format!("{}: {}", path, error)
+}
+
+fn check_version(version: u32) -> Result<(), String> {
+    if version == 0 || version > CONFIG_VERSION {
+        return Err(format!(
+            "version: presets of version {} are not supported, this release reads 1 to {}",
+            version, CONFIG_VERSION
+        ));
+    }
+    Ok(())
+}
+
+// deserializes with the path of the failing field in front of the error,
+// e.g. "source.cube.bits: invalid type"
+fn with_path<'de, T, D>(deserializer: D) -> Result<T, String>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+    D::Error: fmt::Display,
+{
+    serde_path_to_error::deserialize(deserializer).map_err(|error| {
+        let path = error.path().to_string();
+        let inner = error.into_inner().to_string().trim_end().to_string();
+        // "?" stands for a position the parser could not attribute to a field
+        if path == "." || path == "?" {
+            inner
+        } else {
+            format!("{}: {}", path, inner)
+        }
+    })
+}
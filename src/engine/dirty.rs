@@ -0,0 +1,77 @@
+use super::Rect;
+
+// side length of the square tiles changes are tracked in
+pub const DIRTY_TILE_SIZE: usize = 64;
+
+// grid tiles that changed since the last drain
+pub struct DirtyTiles {
+    width: usize,
+    height: usize,
+    columns: usize,
+    marked: Vec<bool>,
+    tiles: Vec<usize>,
+}
+
+impl DirtyTiles {
+    pub fn new(width: usize, height: usize) -> Self {
+        let columns = width.div_ceil(DIRTY_TILE_SIZE);
+        let rows = height.div_ceil(DIRTY_TILE_SIZE);
+        Self {
+            width,
+            height,
+            columns,
+            marked: vec![false; columns * rows],
+            tiles: Vec::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    pub fn mark(&mut self, x: usize, y: usize) {
+        let tile = (y / DIRTY_TILE_SIZE) * self.columns + x / DIRTY_TILE_SIZE;
+        if !self.marked[tile] {
+            self.marked[tile] = true;
+            self.tiles.push(tile);
+        }
+    }
+
+    pub fn mark_all(&mut self) {
+        for tile in 0..self.marked.len() {
+            if !self.marked[tile] {
+                self.marked[tile] = true;
+                self.tiles.push(tile);
+            }
+        }
+    }
+
+    // the marked tiles as rects clipped to the grid, top to bottom and left to
+    // right, with horizontally adjacent tiles merged. clears the marks
+    pub fn take_rects(&mut self) -> Vec<Rect> {
+        self.tiles.sort_unstable();
+
+        let mut rects: Vec<Rect> = Vec::new();
+        let mut previous = None;
+        for &tile in &self.tiles {
+            self.marked[tile] = false;
+            let (column, row) = (tile % self.columns, tile / self.columns);
+            let x = column * DIRTY_TILE_SIZE;
+            let y = row * DIRTY_TILE_SIZE;
+            let width = DIRTY_TILE_SIZE.min(self.width - x);
+
+            match rects.last_mut() {
+                Some(rect) if column > 0 && previous == Some(tile - 1) => rect.width += width,
+                _ => rects.push(Rect {
+                    x,
+                    y,
+                    width,
+                    height: DIRTY_TILE_SIZE.min(self.height - y),
+                }),
+            }
+            previous = Some(tile);
+        }
+        self.tiles.clear();
+        rects
+    }
+}
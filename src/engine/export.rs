@@ -0,0 +1,77 @@
+use super::{decode_share_string, encode_share_string, Engine, Settings};
+
+// text chunk keyword of the share string of a run
+pub const RUN_KEYWORD: &str = "shredder-run";
+
+impl Engine {
+    // the grid as a lossless png. a finished grid is stored as rgb, one with
+    // empty cells as rgba so the gaps stay transparent. with `metadata` text
+    // chunks name the seed, metric, ordering, score, neighborhood and
+    // topology, and a `shredder-run` chunk holds the share string of all
+    // settings, which reproduces the run
+    pub fn encode_png(&self, metadata: bool) -> Result<Vec<u8>, String> {
+        let full = (0..self.cell_count()).all(|cell| self.is_filled(cell));
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut bytes, self.width as u32, self.height as u32);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_color(if full {
+            png::ColorType::Rgb
+        } else {
+            png::ColorType::Rgba
+        });
+
+        if metadata {
+            let settings = &self.settings;
+            let chunks = [
+                (
+                    "Software",
+                    format!("color_shredder {}", env!("CARGO_PKG_VERSION")),
+                ),
+                ("seed", settings.seed.to_string()),
+                ("metric", settings.metric.to_string()),
+                ("ordering", settings.ordering.to_string()),
+                ("score", settings.score.to_string()),
+                ("neighborhood", settings.neighborhood.to_string()),
+                ("topology", settings.topology.to_string()),
+                (RUN_KEYWORD, encode_share_string(settings)),
+            ];
+            for (keyword, text) in chunks.iter() {
+                encoder
+                    .add_text_chunk(keyword.to_string(), text.clone())
+                    .map_err(|error| error.to_string())?;
+            }
+        }
+
+        let mut writer = encoder.write_header().map_err(|error| error.to_string())?;
+        let data = if full {
+            self.pixels
+                .chunks_exact(4)
+                .flat_map(|pixel| pixel[..3].iter().copied())
+                .collect()
+        } else {
+            self.pixels.clone()
+        };
+        writer
+            .write_image_data(&data)
+            .map_err(|error| error.to_string())?;
+        writer.finish().map_err(|error| error.to_string())?;
+        Ok(bytes)
+    }
+}
+
+// the settings in the `shredder-run` chunk of a png written by `encode_png`,
+// None for pngs without one
+pub fn png_run_settings(png: &[u8]) -> Result<Option<Settings>, String> {
+    let reader = png::Decoder::new(png)
+        .read_info()
+        .map_err(|error| error.to_string())?;
+    let chunk = reader
+        .info()
+        .uncompressed_latin1_text
+        .iter()
+        .find(|chunk| chunk.keyword == RUN_KEYWORD);
+    match chunk {
+        Some(chunk) => decode_share_string(&chunk.text).map(Some),
+        None => Ok(None),
+    }
+}
@@ -0,0 +1,79 @@
+// slot value for cells that are not part of the frontier
+const NOT_IN_FRONTIER: u32 = u32::MAX;
+
+// set of open cells with O(1) insert, remove and membership checks
+pub struct Frontier {
+    cells: Vec<usize>,
+    slots: Vec<u32>,
+}
+
+impl Frontier {
+    pub fn new(cell_count: usize) -> Self {
+        Self {
+            cells: Vec::new(),
+            slots: vec![NOT_IN_FRONTIER; cell_count],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn contains(&self, cell: usize) -> bool {
+        self.slots[cell] != NOT_IN_FRONTIER
+    }
+
+    pub fn insert(&mut self, cell: usize) -> bool {
+        if self.contains(cell) {
+            return false;
+        }
+        self.slots[cell] = self.cells.len() as u32;
+        self.cells.push(cell);
+        true
+    }
+
+    pub fn remove(&mut self, cell: usize) -> bool {
+        if !self.contains(cell) {
+            return false;
+        }
+        let slot = self.slots[cell] as usize;
+        self.cells.swap_remove(slot);
+        // the last cell was moved into the freed slot
+        if let Some(&moved) = self.cells.get(slot) {
+            self.slots[moved] = slot as u32;
+        }
+        self.slots[cell] = NOT_IN_FRONTIER;
+        true
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.cells.iter().copied()
+    }
+
+    // the cells in no particular order
+    pub fn as_slice(&self) -> &[usize] {
+        &self.cells
+    }
+}
+
+// a scored frontier cell, ordered by score, then seeded tie rank, then index
+#[derive(Clone, Copy, Debug)]
+pub struct Candidate {
+    pub score: f64,
+    pub rank: u64,
+    pub cell: usize,
+}
+
+impl Candidate {
+    pub fn beats(&self, other: &Candidate) -> bool {
+        self.score
+            .total_cmp(&other.score)
+            .then(self.rank.cmp(&other.rank))
+            .then(self.cell.cmp(&other.cell))
+            .is_lt()
+    }
+}
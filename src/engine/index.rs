@@ -0,0 +1,180 @@
+use super::{Candidate, ColorMetric};
+
+// buckets per axis of the embedding space
+const RESOLUTION: usize = 32;
+// levels per channel sampled from the rgb cube to find the embedding bounds
+const BOUNDS_SAMPLES: u32 = 17;
+// pruning bounds are shrunk by this factor so float rounding can never prune
+// a cell that ties with the best one found so far
+const BOUND_SLACK: f64 = 1.0 - 1e-9;
+
+const NO_BUCKET: u32 = u32::MAX;
+
+// uniform grid over a euclidean metric's embedding space holding frontier cells
+// keyed by the mean embedding of their filled neighbors. nearest queries return
+// exactly the candidate a linear scan with the same keys would pick. only runs
+// scored by the average neighbor have such keys, min neighbor scoring always
+// scans the frontier
+pub struct FrontierIndex {
+    metric: &'static dyn ColorMetric,
+    min: [f64; 3],
+    bucket_size: [f64; 3],
+    buckets: Vec<Vec<(usize, [f64; 3])>>,
+    cell_buckets: Vec<u32>,
+    len: usize,
+}
+
+impl FrontierIndex {
+    pub fn new(metric: &'static dyn ColorMetric, cell_count: usize) -> Self {
+        let (min, max) = embedding_bounds(metric);
+        let mut bucket_size = [0.0; 3];
+        for axis in 0..3 {
+            bucket_size[axis] = ((max[axis] - min[axis]) / RESOLUTION as f64).max(f64::EPSILON);
+        }
+
+        Self {
+            metric,
+            min,
+            bucket_size,
+            buckets: vec![Vec::new(); RESOLUTION * RESOLUTION * RESOLUTION],
+            cell_buckets: vec![NO_BUCKET; cell_count],
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // inserts the cell or moves it to its new key
+    pub fn insert(&mut self, cell: usize, key: [f64; 3]) {
+        self.remove(cell);
+        let bucket = self.bucket_index(self.bucket_coords(&key));
+        self.buckets[bucket].push((cell, key));
+        self.cell_buckets[cell] = bucket as u32;
+        self.len += 1;
+    }
+
+    pub fn remove(&mut self, cell: usize) -> bool {
+        let bucket = self.cell_buckets[cell];
+        if bucket == NO_BUCKET {
+            return false;
+        }
+        let entries = &mut self.buckets[bucket as usize];
+        let slot = entries
+            .iter()
+            .position(|&(entry, _)| entry == cell)
+            .unwrap();
+        entries.swap_remove(slot);
+        self.cell_buckets[cell] = NO_BUCKET;
+        self.len -= 1;
+        true
+    }
+
+    pub fn nearest<F>(&self, target: &[f64; 3], rank: F) -> Option<Candidate>
+    where
+        F: Fn(usize) -> u64,
+    {
+        let center = self.bucket_coords(target);
+        let min_bucket_size = self
+            .bucket_size
+            .iter()
+            .copied()
+            .fold(f64::INFINITY, f64::min);
+        let mut best: Option<Candidate> = None;
+        let mut visited = 0;
+
+        for ring in 0..RESOLUTION {
+            if visited == self.len {
+                break;
+            }
+            // every key in this ring or beyond is more than (ring - 1) buckets away
+            if let Some(best) = best {
+                let reach = (ring.saturating_sub(1)) as f64 * min_bucket_size;
+                if reach * reach * BOUND_SLACK > best.score {
+                    break;
+                }
+            }
+
+            self.for_each_ring_bucket(center, ring, |bucket| {
+                for &(cell, key) in &self.buckets[bucket] {
+                    visited += 1;
+                    let candidate = Candidate {
+                        score: self.metric.distance(target, &key),
+                        rank: rank(cell),
+                        cell,
+                    };
+                    if best.is_none_or(|best| candidate.beats(&best)) {
+                        best = Some(candidate);
+                    }
+                }
+            });
+        }
+        best
+    }
+
+    fn bucket_coords(&self, key: &[f64; 3]) -> [usize; 3] {
+        let mut coords = [0; 3];
+        for axis in 0..3 {
+            let position = ((key[axis] - self.min[axis]) / self.bucket_size[axis]).floor();
+            // keys outside the sampled bounds land in the edge buckets
+            coords[axis] = position.max(0.0).min((RESOLUTION - 1) as f64) as usize;
+        }
+        coords
+    }
+
+    fn bucket_index(&self, coords: [usize; 3]) -> usize {
+        (coords[0] * RESOLUTION + coords[1]) * RESOLUTION + coords[2]
+    }
+
+    // visits the buckets at chebyshev distance `ring` from `center`
+    fn for_each_ring_bucket<F: FnMut(usize)>(&self, center: [usize; 3], ring: usize, mut visit: F) {
+        let range = |axis: usize| {
+            let low = center[axis].saturating_sub(ring);
+            let high = (center[axis] + ring).min(RESOLUTION - 1);
+            low..=high
+        };
+        for x in range(0) {
+            let x_on_shell = x.abs_diff(center[0]) == ring;
+            for y in range(1) {
+                if x_on_shell || y.abs_diff(center[1]) == ring {
+                    for z in range(2) {
+                        visit(self.bucket_index([x, y, z]));
+                    }
+                    continue;
+                }
+                // only the two z faces remain
+                if let Some(z) = center[2].checked_sub(ring) {
+                    visit(self.bucket_index([x, y, z]));
+                }
+                if ring > 0 && center[2] + ring < RESOLUTION {
+                    visit(self.bucket_index([x, y, center[2] + ring]));
+                }
+            }
+        }
+    }
+}
+
+// approximate extent of the embedded rgb cube, exactness is not required
+// since out of range keys are clamped into the edge buckets
+fn embedding_bounds(metric: &dyn ColorMetric) -> ([f64; 3], [f64; 3]) {
+    let mut min = [f64::INFINITY; 3];
+    let mut max = [f64::NEG_INFINITY; 3];
+    let level = |step: u32| (step * 255 / (BOUNDS_SAMPLES - 1)) as u8;
+    for r in 0..BOUNDS_SAMPLES {
+        for g in 0..BOUNDS_SAMPLES {
+            for b in 0..BOUNDS_SAMPLES {
+                let embedded = metric.embed([level(r), level(g), level(b)]);
+                for axis in 0..3 {
+                    min[axis] = min[axis].min(embedded[axis]);
+                    max[axis] = max[axis].max(embedded[axis]);
+                }
+            }
+        }
+    }
+    (min, max)
+}
@@ -0,0 +1,158 @@
+use std::fmt;
+use std::str::FromStr;
+
+// how a run may use a cell
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CellMask {
+    // never filled, growth does not pass through it
+    Forbidden,
+    Allowed,
+    // only filled once every allowed cell is
+    FillLast,
+}
+
+impl fmt::Display for CellMask {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            CellMask::Forbidden => "forbidden",
+            CellMask::Allowed => "allowed",
+            CellMask::FillLast => "fill-last",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for CellMask {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, String> {
+        match name {
+            "forbidden" => Ok(CellMask::Forbidden),
+            "allowed" => Ok(CellMask::Allowed),
+            "fill-last" => Ok(CellMask::FillLast),
+            _ => Err(format!(
+                "unknown cell mask '{}', expected one of forbidden, allowed or fill-last",
+                name
+            )),
+        }
+    }
+}
+
+// per cell restrictions of a run, row by row like the pixels
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mask {
+    width: usize,
+    height: usize,
+    cells: Vec<CellMask>,
+}
+
+impl Mask {
+    // every cell allowed
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![CellMask::Allowed; width * height],
+        }
+    }
+
+    // 0 is forbidden, 255 allowed and anything in between fill last
+    pub fn from_weights(width: usize, height: usize, weights: &[u8]) -> Result<Self, String> {
+        if weights.len() != width * height {
+            return Err(format!(
+                "mask of {} weights does not match the {}x{} grid",
+                weights.len(),
+                width,
+                height
+            ));
+        }
+        let cells = weights
+            .iter()
+            .map(|&weight| match weight {
+                0 => CellMask::Forbidden,
+                255 => CellMask::Allowed,
+                _ => CellMask::FillLast,
+            })
+            .collect();
+        Ok(Self {
+            width,
+            height,
+            cells,
+        })
+    }
+
+    // weights from the alpha channel of an rgba image, e.g. a silhouette or
+    // text rendered by the browser
+    pub fn from_alpha(width: usize, height: usize, rgba: &[u8]) -> Result<Self, String> {
+        if rgba.len() != width * height * 4 {
+            return Err(format!(
+                "mask image of {} bytes does not match the {}x{} grid",
+                rgba.len(),
+                width,
+                height
+            ));
+        }
+        let alpha: Vec<u8> = rgba.chunks_exact(4).map(|pixel| pixel[3]).collect();
+        Self::from_weights(width, height, &alpha)
+    }
+
+    // cells whose centers lie inside the polygons are allowed, the rest is
+    // forbidden. vertices are in cells and overlaps follow the even-odd rule
+    pub fn from_polygons(width: usize, height: usize, polygons: &[Vec<(f64, f64)>]) -> Self {
+        let mut mask = Self::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let (px, py) = (x as f64 + 0.5, y as f64 + 0.5);
+                let containing = polygons
+                    .iter()
+                    .filter(|polygon| contains_point(polygon, px, py))
+                    .count();
+                if containing % 2 == 0 {
+                    mask.cells[y * width + x] = CellMask::Forbidden;
+                }
+            }
+        }
+        mask
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn cell(&self, cell: usize) -> CellMask {
+        self.cells[cell]
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> CellMask {
+        self.cells[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, value: CellMask) {
+        self.cells[y * self.width + x] = value;
+    }
+
+    pub fn count(&self, value: CellMask) -> usize {
+        self.cells.iter().filter(|&&cell| cell == value).count()
+    }
+
+    // cells a run fills, the allowed and fill last ones
+    pub fn placeable_count(&self) -> usize {
+        self.cells.len() - self.count(CellMask::Forbidden)
+    }
+}
+
+// even-odd ray casting
+fn contains_point(polygon: &[(f64, f64)], x: f64, y: f64) -> bool {
+    let mut inside = false;
+    for (i, &(x1, y1)) in polygon.iter().enumerate() {
+        let (x2, y2) = polygon[(i + 1) % polygon.len()];
+        if (y1 > y) != (y2 > y) && x < x1 + (y - y1) / (y2 - y1) * (x2 - x1) {
+            inside = !inside;
+        }
+    }
+    inside
+}
@@ -0,0 +1,308 @@
+use super::Rgb;
+use std::fmt;
+use std::str::FromStr;
+
+// transcendental functions go through libm so wasm and native builds produce
+// bit-identical distances, and with them bit-identical images
+
+// d65 reference white
+const WHITE_X: f64 = 0.95047;
+const WHITE_Y: f64 = 1.0;
+const WHITE_Z: f64 = 1.08883;
+
+// a color distance measured in some embedding of rgb. distances are squared
+// (or squared-like for non-euclidean metrics) so sums behave alike across
+// metrics. metrics are shared between the threads of a parallel search
+pub trait ColorMetric: Sync {
+    fn embed(&self, color: Rgb) -> [f64; 3];
+
+    fn distance(&self, a: &[f64; 3], b: &[f64; 3]) -> f64;
+
+    // true when `distance` is the squared euclidean distance between
+    // embeddings, which lets spatial indexes answer nearest neighbor queries
+    fn is_euclidean(&self) -> bool {
+        false
+    }
+
+    fn color_distance(&self, a: Rgb, b: Rgb) -> f64 {
+        self.distance(&self.embed(a), &self.embed(b))
+    }
+}
+
+pub struct RgbEuclidean;
+pub struct LumaWeighted;
+pub struct Redmean;
+pub struct Cie76;
+pub struct Ciede2000;
+pub struct OklabEuclidean;
+
+impl ColorMetric for RgbEuclidean {
+    fn embed(&self, color: Rgb) -> [f64; 3] {
+        [color[0] as f64, color[1] as f64, color[2] as f64]
+    }
+
+    fn distance(&self, a: &[f64; 3], b: &[f64; 3]) -> f64 {
+        squared_euclidean(a, b)
+    }
+
+    fn is_euclidean(&self) -> bool {
+        true
+    }
+}
+
+// rgb scaled by the square roots of the rec. 601 luma weights
+impl ColorMetric for LumaWeighted {
+    fn embed(&self, color: Rgb) -> [f64; 3] {
+        [
+            color[0] as f64 * libm::sqrt(0.299),
+            color[1] as f64 * libm::sqrt(0.587),
+            color[2] as f64 * libm::sqrt(0.114),
+        ]
+    }
+
+    fn distance(&self, a: &[f64; 3], b: &[f64; 3]) -> f64 {
+        squared_euclidean(a, b)
+    }
+
+    fn is_euclidean(&self) -> bool {
+        true
+    }
+}
+
+// https://www.compuphase.com/cmetric.htm
+impl ColorMetric for Redmean {
+    fn embed(&self, color: Rgb) -> [f64; 3] {
+        [color[0] as f64, color[1] as f64, color[2] as f64]
+    }
+
+    fn distance(&self, a: &[f64; 3], b: &[f64; 3]) -> f64 {
+        let red_mean = (a[0] + b[0]) / 2.0;
+        let (dr, dg, db) = (a[0] - b[0], a[1] - b[1], a[2] - b[2]);
+        (2.0 + red_mean / 256.0) * dr * dr
+            + 4.0 * dg * dg
+            + (2.0 + (255.0 - red_mean) / 256.0) * db * db
+    }
+}
+
+impl ColorMetric for Cie76 {
+    fn embed(&self, color: Rgb) -> [f64; 3] {
+        srgb_to_lab(color)
+    }
+
+    fn distance(&self, a: &[f64; 3], b: &[f64; 3]) -> f64 {
+        squared_euclidean(a, b)
+    }
+
+    fn is_euclidean(&self) -> bool {
+        true
+    }
+}
+
+impl ColorMetric for Ciede2000 {
+    fn embed(&self, color: Rgb) -> [f64; 3] {
+        srgb_to_lab(color)
+    }
+
+    fn distance(&self, a: &[f64; 3], b: &[f64; 3]) -> f64 {
+        let delta_e = ciede2000(a, b);
+        delta_e * delta_e
+    }
+}
+
+impl ColorMetric for OklabEuclidean {
+    fn embed(&self, color: Rgb) -> [f64; 3] {
+        srgb_to_oklab(color)
+    }
+
+    fn distance(&self, a: &[f64; 3], b: &[f64; 3]) -> f64 {
+        squared_euclidean(a, b)
+    }
+
+    fn is_euclidean(&self) -> bool {
+        true
+    }
+}
+
+// the metric choice as stored in settings
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Metric {
+    Rgb,
+    LumaWeighted,
+    Redmean,
+    Cie76,
+    Ciede2000,
+    Oklab,
+}
+
+impl Metric {
+    pub fn implementation(self) -> &'static dyn ColorMetric {
+        match self {
+            Metric::Rgb => &RgbEuclidean,
+            Metric::LumaWeighted => &LumaWeighted,
+            Metric::Redmean => &Redmean,
+            Metric::Cie76 => &Cie76,
+            Metric::Ciede2000 => &Ciede2000,
+            Metric::Oklab => &OklabEuclidean,
+        }
+    }
+}
+
+impl fmt::Display for Metric {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Metric::Rgb => "rgb",
+            Metric::LumaWeighted => "luma-weighted",
+            Metric::Redmean => "redmean",
+            Metric::Cie76 => "cie76",
+            Metric::Ciede2000 => "ciede2000",
+            Metric::Oklab => "oklab",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Metric {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, String> {
+        match name {
+            "rgb" => Ok(Metric::Rgb),
+            "luma-weighted" => Ok(Metric::LumaWeighted),
+            "redmean" => Ok(Metric::Redmean),
+            "cie76" => Ok(Metric::Cie76),
+            "ciede2000" => Ok(Metric::Ciede2000),
+            "oklab" => Ok(Metric::Oklab),
+            _ => Err(format!(
+                "unknown color metric '{}', expected one of rgb, luma-weighted, redmean, \
+                 cie76, ciede2000 or oklab",
+                name
+            )),
+        }
+    }
+}
+
+fn squared_euclidean(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    let (d0, d1, d2) = (a[0] - b[0], a[1] - b[1], a[2] - b[2]);
+    d0 * d0 + d1 * d1 + d2 * d2
+}
+
+pub fn srgb_to_linear(channel: u8) -> f64 {
+    let c = channel as f64 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        libm::pow((c + 0.055) / 1.055, 2.4)
+    }
+}
+
+// cie l*a*b* relative to d65
+pub fn srgb_to_lab(color: Rgb) -> [f64; 3] {
+    let [r, g, b] = [
+        srgb_to_linear(color[0]),
+        srgb_to_linear(color[1]),
+        srgb_to_linear(color[2]),
+    ];
+    let x = 0.4124564 * r + 0.3575761 * g + 0.1804375 * b;
+    let y = 0.2126729 * r + 0.7151522 * g + 0.0721750 * b;
+    let z = 0.0193339 * r + 0.1191920 * g + 0.9503041 * b;
+
+    let f = |t: f64| {
+        let delta: f64 = 6.0 / 29.0;
+        if t > delta * delta * delta {
+            libm::cbrt(t)
+        } else {
+            t / (3.0 * delta * delta) + 4.0 / 29.0
+        }
+    };
+    let (fx, fy, fz) = (f(x / WHITE_X), f(y / WHITE_Y), f(z / WHITE_Z));
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+// https://bottosson.github.io/posts/oklab/
+pub fn srgb_to_oklab(color: Rgb) -> [f64; 3] {
+    let [r, g, b] = [
+        srgb_to_linear(color[0]),
+        srgb_to_linear(color[1]),
+        srgb_to_linear(color[2]),
+    ];
+    let l = libm::cbrt(0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b);
+    let m = libm::cbrt(0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b);
+    let s = libm::cbrt(0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b);
+    [
+        0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+        1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+        0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+    ]
+}
+
+// ciede2000 color difference between two l*a*b* colors, following
+// sharma, wu and dalal (2005)
+pub fn ciede2000(lab1: &[f64; 3], lab2: &[f64; 3]) -> f64 {
+    let [l1, a1, b1] = *lab1;
+    let [l2, a2, b2] = *lab2;
+    let pow7_25 = libm::pow(25.0, 7.0);
+
+    let c_bar = (libm::sqrt(a1 * a1 + b1 * b1) + libm::sqrt(a2 * a2 + b2 * b2)) / 2.0;
+    let c_bar7 = libm::pow(c_bar, 7.0);
+    let g = 0.5 * (1.0 - libm::sqrt(c_bar7 / (c_bar7 + pow7_25)));
+
+    let (a1p, a2p) = ((1.0 + g) * a1, (1.0 + g) * a2);
+    let (c1p, c2p) = (
+        libm::sqrt(a1p * a1p + b1 * b1),
+        libm::sqrt(a2p * a2p + b2 * b2),
+    );
+    let hue_angle = |b: f64, ap: f64| {
+        if b == 0.0 && ap == 0.0 {
+            return 0.0;
+        }
+        let h = libm::atan2(b, ap).to_degrees();
+        if h < 0.0 {
+            h + 360.0
+        } else {
+            h
+        }
+    };
+    let (h1p, h2p) = (hue_angle(b1, a1p), hue_angle(b2, a2p));
+
+    let delta_lp = l2 - l1;
+    let delta_cp = c2p - c1p;
+    let delta_hp = if c1p * c2p == 0.0 {
+        0.0
+    } else if (h2p - h1p).abs() <= 180.0 {
+        h2p - h1p
+    } else if h2p - h1p > 180.0 {
+        h2p - h1p - 360.0
+    } else {
+        h2p - h1p + 360.0
+    };
+    let delta_big_hp = 2.0 * libm::sqrt(c1p * c2p) * libm::sin((delta_hp / 2.0).to_radians());
+
+    let l_bar_p = (l1 + l2) / 2.0;
+    let c_bar_p = (c1p + c2p) / 2.0;
+    let h_bar_p = if c1p * c2p == 0.0 {
+        h1p + h2p
+    } else if (h1p - h2p).abs() <= 180.0 {
+        (h1p + h2p) / 2.0
+    } else if h1p + h2p < 360.0 {
+        (h1p + h2p + 360.0) / 2.0
+    } else {
+        (h1p + h2p - 360.0) / 2.0
+    };
+
+    let cos_deg = |degrees: f64| libm::cos(degrees.to_radians());
+    let t = 1.0 - 0.17 * cos_deg(h_bar_p - 30.0)
+        + 0.24 * cos_deg(2.0 * h_bar_p)
+        + 0.32 * cos_deg(3.0 * h_bar_p + 6.0)
+        - 0.20 * cos_deg(4.0 * h_bar_p - 63.0);
+    let delta_theta = 30.0 * libm::exp(-libm::pow((h_bar_p - 275.0) / 25.0, 2.0));
+    let c_bar_p7 = libm::pow(c_bar_p, 7.0);
+    let r_c = 2.0 * libm::sqrt(c_bar_p7 / (c_bar_p7 + pow7_25));
+    let l_offset = (l_bar_p - 50.0) * (l_bar_p - 50.0);
+    let s_l = 1.0 + 0.015 * l_offset / libm::sqrt(20.0 + l_offset);
+    let s_c = 1.0 + 0.045 * c_bar_p;
+    let s_h = 1.0 + 0.015 * c_bar_p * t;
+    let r_t = -libm::sin((2.0 * delta_theta).to_radians()) * r_c;
+
+    let (dl, dc, dh) = (delta_lp / s_l, delta_cp / s_c, delta_big_hp / s_h);
+    libm::sqrt(dl * dl + dc * dc + dh * dh + r_t * dc * dh)
+}
@@ -0,0 +1,621 @@
+mod animation;
+pub use animation::*;
+mod checkpoint;
+pub use checkpoint::*;
+mod codec;
+pub use codec::*;
+mod config;
+pub use config::*;
+mod dirty;
+pub use dirty::*;
+mod export;
+pub use export::*;
+mod frontier;
+pub use frontier::*;
+mod index;
+pub use index::*;
+mod mask;
+pub use mask::*;
+mod metric;
+pub use metric::*;
+mod neighborhood;
+pub use neighborhood::*;
+mod ordering;
+pub use ordering::*;
+#[cfg(feature = "parallel")]
+mod parallel;
+#[cfg(feature = "parallel")]
+pub use parallel::*;
+mod progress;
+pub use progress::*;
+mod protocol;
+pub use protocol::*;
+mod recording;
+pub use recording::*;
+mod score;
+pub use score::*;
+mod replay;
+pub use replay::*;
+mod seed_point;
+pub use seed_point::*;
+mod settings;
+pub use settings::*;
+mod share;
+pub use share::*;
+mod source;
+pub use source::*;
+mod topology;
+pub use topology::*;
+mod validate;
+pub use validate::*;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+pub type Rgb = [u8; 3];
+
+// frontier cells scored per task by a parallel scan, smaller frontiers are
+// scanned on the calling thread
+#[cfg(feature = "parallel")]
+const SCAN_CHUNK: usize = 512;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Placement {
+    pub cell: usize,
+    pub color: Rgb,
+}
+
+// a rectangle of grid cells
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+// places one color per step next to the already placed neighbors it matches best
+pub struct Engine {
+    settings: Settings,
+    width: usize,
+    height: usize,
+    rng: ChaCha8Rng,
+    metric: &'static dyn ColorMetric,
+    kernel: Kernel,
+    // rgba, alpha is 0 for empty cells and 255 for filled ones
+    pixels: Vec<u8>,
+    colors: Vec<Rgb>,
+    next_color: usize,
+    frontier: Frontier,
+    // fill last cells next to filled ones, they join the frontier once every
+    // allowed cell is filled
+    deferred: Frontier,
+    allowed_left: usize,
+    fill_last_left: usize,
+    // only used for average scoring under euclidean metrics
+    index: Option<FrontierIndex>,
+    placed: usize,
+    dirty: DirtyTiles,
+    recording: Option<Recording>,
+    #[cfg(feature = "parallel")]
+    parallelism: Parallelism,
+    // cells searched ahead for the next colors by a batched parallel search
+    #[cfg(feature = "parallel")]
+    proposals: std::collections::VecDeque<Option<usize>>,
+}
+
+impl Engine {
+    pub fn new(settings: Settings) -> Result<Self, String> {
+        check_settings(&settings)?;
+        let (rng, colors, _) = run_colors(&settings);
+        Ok(Self::with_colors(settings, rng, colors))
+    }
+
+    // an empty grid for checked settings, `colors` in placement order
+    fn with_colors(settings: Settings, rng: ChaCha8Rng, colors: Vec<Rgb>) -> Self {
+        let (width, height) = (settings.width, settings.height);
+        let cell_count = width * height;
+        let metric = settings.metric.implementation();
+        let kernel = settings.neighborhood.kernel();
+        let (allowed_left, fill_last_left) = match &settings.mask {
+            Some(mask) => (
+                mask.count(CellMask::Allowed),
+                mask.count(CellMask::FillLast),
+            ),
+            None => (cell_count, 0),
+        };
+        let index = if indexable(&settings) {
+            Some(FrontierIndex::new(metric, cell_count))
+        } else {
+            None
+        };
+
+        Self {
+            settings,
+            width,
+            height,
+            rng,
+            metric,
+            kernel,
+            pixels: vec![0; cell_count * 4],
+            colors,
+            next_color: 0,
+            frontier: Frontier::new(cell_count),
+            deferred: Frontier::new(cell_count),
+            allowed_left,
+            fill_last_left,
+            index,
+            placed: 0,
+            dirty: DirtyTiles::new(width, height),
+            recording: None,
+            #[cfg(feature = "parallel")]
+            parallelism: Parallelism::Serial,
+            #[cfg(feature = "parallel")]
+            proposals: std::collections::VecDeque::new(),
+        }
+    }
+
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn cell_count(&self) -> usize {
+        self.width * self.height
+    }
+
+    pub fn placed(&self) -> usize {
+        self.placed
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.colors.len() - self.next_color
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    // regions of `pixels` changed since the last call, for partial uploads
+    pub fn take_dirty_rects(&mut self) -> Vec<Rect> {
+        self.dirty.take_rects()
+    }
+
+    pub fn has_dirty_rects(&self) -> bool {
+        !self.dirty.is_empty()
+    }
+
+    // flags the whole grid, e.g. after the texture it was uploaded to got lost
+    pub fn mark_all_dirty(&mut self) {
+        self.dirty.mark_all();
+    }
+
+    pub fn frontier(&self) -> &Frontier {
+        &self.frontier
+    }
+
+    pub fn uses_frontier_index(&self) -> bool {
+        self.index.is_some()
+    }
+
+    // switches between the frontier index and a linear scan of the frontier,
+    // both pick the same cells. the index is only available for average
+    // scoring under a euclidean metric
+    pub fn set_frontier_index(&mut self, enabled: bool) {
+        if !enabled || !indexable(&self.settings) {
+            self.index = None;
+            return;
+        }
+        if self.index.is_some() {
+            return;
+        }
+
+        let mut index = FrontierIndex::new(self.metric, self.cell_count());
+        for cell in self.frontier.iter() {
+            index.insert(cell, self.neighbor_mean(cell).unwrap());
+        }
+        self.index = Some(index);
+    }
+
+    // records the order of all placements from here on, only possible before
+    // the first step so the recording covers the whole run
+    pub fn start_recording(&mut self) -> Result<(), String> {
+        if self.placed > 0 {
+            return Err(format!(
+                "cannot start recording after {} placements",
+                self.placed
+            ));
+        }
+        self.recording = Some(Recording::new(self.width, self.height));
+        Ok(())
+    }
+
+    pub fn recording(&self) -> Option<&Recording> {
+        self.recording.as_ref()
+    }
+
+    pub fn color_at(&self, x: usize, y: usize) -> Option<Rgb> {
+        self.cell_color(y * self.width + x)
+    }
+
+    pub fn cell_color(&self, cell: usize) -> Option<Rgb> {
+        let pixel = &self.pixels[cell * 4..cell * 4 + 4];
+        if pixel[3] == 0 {
+            return None;
+        }
+        Some([pixel[0], pixel[1], pixel[2]])
+    }
+
+    // place the next color, returns None once every color has been placed
+    pub fn step(&mut self) -> Option<Placement> {
+        let color = *self.colors.get(self.next_color)?;
+        // seed points come first and hold the first colors
+        let cell = match self.settings.seed_points.get(self.next_color) {
+            Some(point) => point.y * self.width + point.x,
+            None => self.choose_cell(color)?,
+        };
+
+        self.next_color += 1;
+        self.place(cell, color);
+        Some(Placement { cell, color })
+    }
+
+    // run up to `count` steps, returns how many colors were placed
+    pub fn step_n(&mut self, count: usize) -> usize {
+        let mut placed = 0;
+        while placed < count && self.step().is_some() {
+            placed += 1;
+        }
+        placed
+    }
+
+    // steps until `budget_ms` has passed on `now_ms` or `step_limit` colors
+    // were placed (0 for no limit), returns how many colors were placed. the
+    // clock is only read every few steps to keep its overhead low
+    pub fn step_within<F>(&mut self, budget_ms: f64, step_limit: usize, mut now_ms: F) -> usize
+    where
+        F: FnMut() -> f64,
+    {
+        const CLOCK_INTERVAL: usize = 16;
+
+        let deadline = now_ms() + budget_ms;
+        let step_limit = if step_limit == 0 {
+            usize::MAX
+        } else {
+            step_limit
+        };
+        let mut placed = 0;
+        while placed < step_limit {
+            if placed % CLOCK_INTERVAL == 0 && now_ms() >= deadline {
+                break;
+            }
+            if self.step().is_none() {
+                break;
+            }
+            placed += 1;
+        }
+        placed
+    }
+
+    pub fn run(&mut self) {
+        while self.step().is_some() {}
+    }
+
+    fn is_filled(&self, cell: usize) -> bool {
+        self.pixels[cell * 4 + 3] != 0
+    }
+
+    fn cell_mask(&self, cell: usize) -> CellMask {
+        match &self.settings.mask {
+            Some(mask) => mask.cell(cell),
+            None => CellMask::Allowed,
+        }
+    }
+
+    // neighbor cells with their weights
+    fn neighbors(&self, cell: usize) -> impl Iterator<Item = (usize, f64)> + '_ {
+        let (x, y) = (cell % self.width, cell / self.width);
+        self.kernel
+            .offsets(y)
+            .iter()
+            .filter_map(move |&(dx, dy, weight)| {
+                self.offset_cell(x, y, dx, dy)
+                    .map(|neighbor| (neighbor, weight))
+            })
+    }
+
+    // cells that have `cell` as a neighbor
+    fn reverse_neighbors(&self, cell: usize) -> Vec<usize> {
+        let (x, y) = (cell % self.width, cell / self.width);
+        let (edge_x, edge_y) = self.settings.topology.edges();
+
+        let mut cells = Vec::new();
+        for parity in 0..2 {
+            for &(dx, dy, _) in self.kernel.offsets(parity) {
+                for source_y in edge_y.sources(y, dy, self.height) {
+                    if source_y % 2 != parity {
+                        continue;
+                    }
+                    for source_x in edge_x.sources(x, dx, self.width) {
+                        cells.push(source_y * self.width + source_x);
+                    }
+                }
+            }
+        }
+        cells
+    }
+
+    fn offset_cell(&self, x: usize, y: usize, dx: isize, dy: isize) -> Option<usize> {
+        let (edge_x, edge_y) = self.settings.topology.edges();
+        let nx = edge_x.resolve(x as isize + dx, self.width)?;
+        let ny = edge_y.resolve(y as isize + dy, self.height)?;
+        Some(ny * self.width + nx)
+    }
+
+    // used for the first placement and whenever growth got enclosed
+    fn random_empty_cell(&mut self) -> Option<usize> {
+        let (class, empty_count) = if self.allowed_left > 0 {
+            (CellMask::Allowed, self.allowed_left)
+        } else {
+            (CellMask::FillLast, self.fill_last_left)
+        };
+        if empty_count == 0 {
+            return None;
+        }
+        // draw a u64 so wasm32 and native consume the rng identically
+        let nth = self.rng.gen_range(0..empty_count as u64) as usize;
+        (0..self.cell_count())
+            .filter(|&cell| !self.is_filled(cell) && self.cell_mask(cell) == class)
+            .nth(nth)
+    }
+
+    // the cell for a color that is not a seed point's
+    fn choose_cell(&mut self, color: Rgb) -> Option<usize> {
+        #[cfg(feature = "parallel")]
+        {
+            if let Some(cell) = self.proposed_cell() {
+                return Some(cell);
+            }
+        }
+        match self.best_frontier_cell(color) {
+            Some(cell) => Some(cell),
+            None => self.random_empty_cell(),
+        }
+    }
+
+    fn best_frontier_cell(&self, color: Rgb) -> Option<usize> {
+        let target = self.metric.embed(color);
+        let seed = self.settings.seed;
+        if let Some(index) = &self.index {
+            return index
+                .nearest(&target, |cell| tie_rank(seed, cell))
+                .map(|candidate| candidate.cell);
+        }
+
+        #[cfg(feature = "parallel")]
+        {
+            if self.parallelism != Parallelism::Serial {
+                return self
+                    .par_scan_frontier(&target)
+                    .map(|candidate| candidate.cell);
+            }
+        }
+        self.scan_frontier(self.frontier.as_slice(), &target)
+            .map(|candidate| candidate.cell)
+    }
+
+    // the best of `cells` by a linear scan
+    fn scan_frontier(&self, cells: &[usize], target: &[f64; 3]) -> Option<Candidate> {
+        let seed = self.settings.seed;
+        let mut best: Option<Candidate> = None;
+        for &cell in cells {
+            let candidate = Candidate {
+                score: self.score(cell, target),
+                rank: tie_rank(seed, cell),
+                cell,
+            };
+            if best.is_none_or(|best| candidate.beats(&best)) {
+                best = Some(candidate);
+            }
+        }
+        best
+    }
+
+    // the cell searched ahead for the next color in batched mode, unless an
+    // earlier color of its batch took it
+    #[cfg(feature = "parallel")]
+    fn proposed_cell(&mut self) -> Option<usize> {
+        let batch = match self.parallelism {
+            Parallelism::Batched(batch) => batch,
+            _ => return None,
+        };
+        if self.proposals.is_empty() {
+            let end = (self.next_color + batch).min(self.colors.len());
+            self.proposals = self.colors[self.next_color..end]
+                .par_iter()
+                .map(|&color| self.best_frontier_cell(color))
+                .collect();
+        }
+        self.proposals
+            .pop_front()
+            .flatten()
+            .filter(|&cell| !self.is_filled(cell))
+    }
+
+    // the same candidate as a serial scan, candidates are totally ordered so
+    // the order in which chunks are combined does not matter
+    #[cfg(feature = "parallel")]
+    fn par_scan_frontier(&self, target: &[f64; 3]) -> Option<Candidate> {
+        let cells = self.frontier.as_slice();
+        if cells.len() <= SCAN_CHUNK {
+            return self.scan_frontier(cells, target);
+        }
+        cells
+            .par_chunks(SCAN_CHUNK)
+            .filter_map(|chunk| self.scan_frontier(chunk, target))
+            .reduce_with(|a, b| if b.beats(&a) { b } else { a })
+    }
+
+    fn filled_neighbors(&self, cell: usize) -> impl Iterator<Item = (Rgb, f64)> + '_ {
+        self.neighbors(cell).filter_map(move |(neighbor, weight)| {
+            self.cell_color(neighbor).map(|color| (color, weight))
+        })
+    }
+
+    fn neighbor_mean(&self, cell: usize) -> Option<[f64; 3]> {
+        mean_embedding(self.metric, self.filled_neighbors(cell))
+    }
+
+    fn score(&self, cell: usize, target: &[f64; 3]) -> f64 {
+        self.settings
+            .score
+            .score(self.metric, target, self.filled_neighbors(cell))
+    }
+
+    fn place(&mut self, cell: usize, color: Rgb) {
+        self.pixels[cell * 4..cell * 4 + 4].copy_from_slice(&[color[0], color[1], color[2], 255]);
+        self.placed += 1;
+        self.dirty.mark(cell % self.width, cell / self.width);
+        if let Some(recording) = &mut self.recording {
+            recording.push(cell);
+        }
+        self.frontier.remove(cell);
+        self.deferred.remove(cell);
+        match self.cell_mask(cell) {
+            CellMask::Allowed => self.allowed_left -= 1,
+            CellMask::FillLast => self.fill_last_left -= 1,
+            CellMask::Forbidden => unreachable!("placed a color on a forbidden cell"),
+        }
+
+        // the frontier holds the empty cells with a filled neighbor, forbidden
+        // cells never join it and fill last cells wait for the allowed ones
+        let mut open_neighbors = Vec::new();
+        for neighbor in self.reverse_neighbors(cell) {
+            if self.is_filled(neighbor) {
+                continue;
+            }
+            match self.cell_mask(neighbor) {
+                CellMask::Forbidden => {}
+                CellMask::FillLast if self.allowed_left > 0 => {
+                    self.deferred.insert(neighbor);
+                }
+                _ => {
+                    self.frontier.insert(neighbor);
+                    open_neighbors.push(neighbor);
+                }
+            }
+        }
+        if self.allowed_left == 0 && !self.deferred.is_empty() {
+            let released: Vec<usize> = self.deferred.iter().collect();
+            for neighbor in released {
+                self.deferred.remove(neighbor);
+                self.frontier.insert(neighbor);
+                open_neighbors.push(neighbor);
+            }
+        }
+
+        // the neighbor means around the placed cell changed, re-key them
+        let mut index = self.index.take();
+        if let Some(index) = &mut index {
+            index.remove(cell);
+            for &neighbor in &open_neighbors {
+                index.insert(neighbor, self.neighbor_mean(neighbor).unwrap());
+            }
+        }
+        self.index = index;
+    }
+}
+
+fn check_settings(settings: &Settings) -> Result<(), String> {
+    let (width, height) = (settings.width, settings.height);
+    if width == 0 || height == 0 {
+        return Err(format!("grid must not be empty, got {}x{}", width, height));
+    }
+    // frontier slots and checkpoints store cells as u32
+    if width
+        .checked_mul(height)
+        .is_none_or(|cells| cells > u32::MAX as usize)
+    {
+        return Err(format!("grid of {}x{} cells is too large", width, height));
+    }
+    if let Some(mask) = &settings.mask {
+        if (mask.width(), mask.height()) != (width, height) {
+            return Err(format!(
+                "the mask is {}x{} but the grid is {}x{}",
+                mask.width(),
+                mask.height(),
+                width,
+                height
+            ));
+        }
+    }
+    settings.source.check(settings.placeable_cells())?;
+    settings.ordering.check()?;
+    settings.neighborhood.check()?;
+    check_seed_points(&settings.seed_points, width, height)?;
+    if let Some(mask) = &settings.mask {
+        let forbidden = settings
+            .seed_points
+            .iter()
+            .position(|point| mask.get(point.x, point.y) == CellMask::Forbidden);
+        if let Some(i) = forbidden {
+            let point = settings.seed_points[i];
+            return Err(format!(
+                "seed point {} at {},{} lies on a forbidden cell",
+                i, point.x, point.y
+            ));
+        }
+    }
+    if settings.neighborhood == Neighborhood::Hexagonal
+        && settings.topology.edges().1 == Edge::Wrap
+        && height % 2 != 0
+    {
+        return Err(format!(
+            "a hexagonal grid needs an even height to wrap vertically, got {}",
+            height
+        ));
+    }
+    Ok(())
+}
+
+// the rng after drawing the colors, the colors in placement order with seed
+// point colors first, and the source colors explicit seed colors replaced
+fn run_colors(settings: &Settings) -> (ChaCha8Rng, Vec<Rgb>, Vec<(Rgb, Rgb)>) {
+    let mut rng = ChaCha8Rng::seed_from_u64(settings.seed);
+    let mut colors = settings.source.colors(&mut rng);
+    settings.ordering.apply(&mut colors, &mut rng);
+    let substituted = take_seed_colors(
+        &settings.seed_points,
+        &mut colors,
+        settings.metric.implementation(),
+    );
+    (rng, colors, substituted)
+}
+
+fn indexable(settings: &Settings) -> bool {
+    settings.score == PlacementScore::AverageNeighbor
+        && settings.metric.implementation().is_euclidean()
+}
+
+// seeded per-cell priority for breaking score ties, independent of the order
+// in which frontier cells are visited
+fn tie_rank(seed: u64, cell: usize) -> u64 {
+    // splitmix64 finalizer
+    let mut z = seed.wrapping_add((cell as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
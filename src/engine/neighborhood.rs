@@ -0,0 +1,209 @@
+use std::f64::consts::FRAC_1_SQRT_2;
+use std::fmt;
+use std::str::FromStr;
+
+// largest offset along either axis a kernel may reach
+const MAX_REACH: isize = 8;
+
+// (dx, dy, weight) of a cell's neighbor relative to the cell
+pub type Offset = (isize, isize, f64);
+
+// moore neighborhood, diagonals weighted by their inverse distance
+const MOORE: [Offset; 8] = [
+    (-1, -1, FRAC_1_SQRT_2),
+    (0, -1, 1.0),
+    (1, -1, FRAC_1_SQRT_2),
+    (-1, 0, 1.0),
+    (1, 0, 1.0),
+    (-1, 1, FRAC_1_SQRT_2),
+    (0, 1, 1.0),
+    (1, 1, FRAC_1_SQRT_2),
+];
+
+const VON_NEUMANN: [Offset; 4] = [(0, -1, 1.0), (-1, 0, 1.0), (1, 0, 1.0), (0, 1, 1.0)];
+
+// odd rows are shifted right by half a cell, so the diagonal neighbors depend
+// on the parity of the row
+const HEX_EVEN_ROW: [Offset; 6] = [
+    (-1, -1, 1.0),
+    (0, -1, 1.0),
+    (-1, 0, 1.0),
+    (1, 0, 1.0),
+    (-1, 1, 1.0),
+    (0, 1, 1.0),
+];
+const HEX_ODD_ROW: [Offset; 6] = [
+    (0, -1, 1.0),
+    (1, -1, 1.0),
+    (-1, 0, 1.0),
+    (1, 0, 1.0),
+    (0, 1, 1.0),
+    (1, 1, 1.0),
+];
+
+// which cells count as neighbors when scoring frontier cells and growing the
+// frontier
+#[derive(Clone, Debug, PartialEq)]
+pub enum Neighborhood {
+    VonNeumann,
+    Moore,
+    // every cell within `radius` cells, weighted by inverse distance
+    Disk { radius: u32 },
+    // hexagonal cells laid out as an offset grid
+    Hexagonal,
+    // user supplied offsets with their weights
+    Custom(Vec<Offset>),
+}
+
+impl Neighborhood {
+    pub fn check(&self) -> Result<(), String> {
+        match self {
+            Neighborhood::Disk { radius } if !(1..=MAX_REACH as u32).contains(radius) => {
+                Err(format!(
+                    "disk radius must be within 1 and {}, got {}",
+                    MAX_REACH, radius
+                ))
+            }
+            Neighborhood::Custom(offsets) => {
+                if offsets.is_empty() {
+                    return Err(String::from("a custom kernel needs at least one offset"));
+                }
+                for (i, &(dx, dy, weight)) in offsets.iter().enumerate() {
+                    if (dx, dy) == (0, 0) {
+                        return Err(String::from("a custom kernel must not contain 0,0"));
+                    }
+                    if dx.abs() > MAX_REACH || dy.abs() > MAX_REACH {
+                        return Err(format!(
+                            "kernel offset {},{} reaches further than {} cells",
+                            dx, dy, MAX_REACH
+                        ));
+                    }
+                    if !(weight.is_finite() && weight > 0.0) {
+                        return Err(format!(
+                            "kernel weight of {},{} must be positive, got {}",
+                            dx, dy, weight
+                        ));
+                    }
+                    if offsets[..i].iter().any(|&(x, y, _)| (x, y) == (dx, dy)) {
+                        return Err(format!("kernel offset {},{} appears twice", dx, dy));
+                    }
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn kernel(&self) -> Kernel {
+        let rows = match self {
+            Neighborhood::VonNeumann => [VON_NEUMANN.to_vec(), VON_NEUMANN.to_vec()],
+            Neighborhood::Moore => [MOORE.to_vec(), MOORE.to_vec()],
+            Neighborhood::Disk { radius } => {
+                let reach = *radius as isize;
+                let mut offsets = Vec::new();
+                for dy in -reach..=reach {
+                    for dx in -reach..=reach {
+                        let squared = dx * dx + dy * dy;
+                        if squared != 0 && squared <= reach * reach {
+                            offsets.push((dx, dy, 1.0 / libm::sqrt(squared as f64)));
+                        }
+                    }
+                }
+                [offsets.clone(), offsets]
+            }
+            Neighborhood::Hexagonal => [HEX_EVEN_ROW.to_vec(), HEX_ODD_ROW.to_vec()],
+            Neighborhood::Custom(offsets) => [offsets.clone(), offsets.clone()],
+        };
+        Kernel { rows }
+    }
+}
+
+impl fmt::Display for Neighborhood {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Neighborhood::VonNeumann => write!(f, "von-neumann"),
+            Neighborhood::Moore => write!(f, "moore"),
+            Neighborhood::Disk { radius } => write!(f, "disk={}", radius),
+            Neighborhood::Hexagonal => write!(f, "hex"),
+            Neighborhood::Custom(offsets) => {
+                write!(f, "custom=")?;
+                for (i, (dx, dy, weight)) in offsets.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ";")?;
+                    }
+                    write!(f, "{},{},{}", dx, dy, weight)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl FromStr for Neighborhood {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, String> {
+        let neighborhood = match name {
+            "von-neumann" => Neighborhood::VonNeumann,
+            "moore" => Neighborhood::Moore,
+            "hex" => Neighborhood::Hexagonal,
+            _ => {
+                if let Some(radius) = name.strip_prefix("disk=") {
+                    Neighborhood::Disk {
+                        radius: radius
+                            .parse()
+                            .map_err(|_| format!("invalid disk radius '{}'", radius))?,
+                    }
+                } else if let Some(offsets) = name.strip_prefix("custom=") {
+                    Neighborhood::Custom(
+                        offsets
+                            .split(';')
+                            .map(parse_offset)
+                            .collect::<Result<_, _>>()?,
+                    )
+                } else {
+                    return Err(format!(
+                        "unknown neighborhood '{}', expected one of von-neumann, moore, \
+                         disk=<radius>, hex or custom=<dx,dy,weight;...>",
+                        name
+                    ));
+                }
+            }
+        };
+        neighborhood.check()?;
+        Ok(neighborhood)
+    }
+}
+
+fn parse_offset(offset: &str) -> Result<Offset, String> {
+    let invalid = || format!("invalid kernel offset '{}', expected dx,dy,weight", offset);
+    let mut parts = offset.split(',').map(str::trim);
+    let dx = parts
+        .next()
+        .and_then(|dx| dx.parse().ok())
+        .ok_or_else(invalid)?;
+    let dy = parts
+        .next()
+        .and_then(|dy| dy.parse().ok())
+        .ok_or_else(invalid)?;
+    let weight = parts
+        .next()
+        .and_then(|weight| weight.parse().ok())
+        .ok_or_else(invalid)?;
+    if parts.next().is_some() {
+        return Err(invalid());
+    }
+    Ok((dx, dy, weight))
+}
+
+// the offsets of a neighborhood, resolved per row parity
+#[derive(Clone, Debug, PartialEq)]
+pub struct Kernel {
+    rows: [Vec<Offset>; 2],
+}
+
+impl Kernel {
+    pub fn offsets(&self, y: usize) -> &[Offset] {
+        &self.rows[y % 2]
+    }
+}
@@ -0,0 +1,204 @@
+use super::Rgb;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::fmt;
+use std::str::FromStr;
+
+// order in which the colors of a run get placed
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorOrdering {
+    Shuffle,
+    Hue,
+    Luminance,
+    Saturation,
+    Hilbert,
+    Morton,
+    // sorted by hue with random jitter, strength 0 is a plain shuffle and 1 a
+    // plain hue sort
+    RandomHueBias { strength: f64 },
+}
+
+impl ColorOrdering {
+    pub fn check(&self) -> Result<(), String> {
+        match self {
+            ColorOrdering::RandomHueBias { strength } if !(0.0..=1.0).contains(strength) => Err(
+                format!("hue bias strength must be within 0 and 1, got {}", strength),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    // colors are always shuffled first so that sort ties still depend on the seed
+    pub fn apply<R: Rng>(&self, colors: &mut Vec<Rgb>, rng: &mut R) {
+        colors.shuffle(rng);
+
+        match *self {
+            ColorOrdering::Shuffle => {}
+            ColorOrdering::Hue => colors.sort_by(|a, b| hue(*a).total_cmp(&hue(*b))),
+            ColorOrdering::Luminance => colors.sort_by_key(|&color| luma(color)),
+            ColorOrdering::Saturation => {
+                colors.sort_by(|a, b| saturation(*a).total_cmp(&saturation(*b)))
+            }
+            ColorOrdering::Hilbert => colors.sort_by_key(|&color| hilbert_index(color)),
+            ColorOrdering::Morton => colors.sort_by_key(|&color| morton_index(color)),
+            ColorOrdering::RandomHueBias { strength } => {
+                let mut keyed: Vec<(f64, Rgb)> = colors
+                    .iter()
+                    .map(|&color| {
+                        let jitter: f64 = rng.gen();
+                        (
+                            strength * hue(color) / 6.0 + (1.0 - strength) * jitter,
+                            color,
+                        )
+                    })
+                    .collect();
+                keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+                colors.clear();
+                colors.extend(keyed.into_iter().map(|(_, color)| color));
+            }
+        }
+    }
+}
+
+impl fmt::Display for ColorOrdering {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ColorOrdering::Shuffle => write!(f, "shuffle"),
+            ColorOrdering::Hue => write!(f, "hue"),
+            ColorOrdering::Luminance => write!(f, "luminance"),
+            ColorOrdering::Saturation => write!(f, "saturation"),
+            ColorOrdering::Hilbert => write!(f, "hilbert"),
+            ColorOrdering::Morton => write!(f, "morton"),
+            ColorOrdering::RandomHueBias { strength } => write!(f, "hue-bias={}", strength),
+        }
+    }
+}
+
+impl FromStr for ColorOrdering {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, String> {
+        let ordering = match name {
+            "shuffle" => ColorOrdering::Shuffle,
+            "hue" => ColorOrdering::Hue,
+            "luminance" => ColorOrdering::Luminance,
+            "saturation" => ColorOrdering::Saturation,
+            "hilbert" => ColorOrdering::Hilbert,
+            "morton" => ColorOrdering::Morton,
+            _ => match name.strip_prefix("hue-bias=") {
+                Some(strength) => ColorOrdering::RandomHueBias {
+                    strength: strength
+                        .parse()
+                        .map_err(|_| format!("invalid hue bias strength '{}'", strength))?,
+                },
+                None => {
+                    return Err(format!(
+                        "unknown color ordering '{}', expected one of shuffle, hue, luminance, \
+                         saturation, hilbert, morton or hue-bias=<0..1>",
+                        name
+                    ))
+                }
+            },
+        };
+        ordering.check()?;
+        Ok(ordering)
+    }
+}
+
+// hsv hue in [0, 6), grays are 0
+pub fn hue(color: Rgb) -> f64 {
+    let [r, g, b] = [color[0] as f64, color[1] as f64, color[2] as f64];
+    let max = r.max(g).max(b);
+    let chroma = max - r.min(g).min(b);
+    if chroma == 0.0 {
+        return 0.0;
+    }
+
+    let hue = if max == r {
+        (g - b) / chroma
+    } else if max == g {
+        (b - r) / chroma + 2.0
+    } else {
+        (r - g) / chroma + 4.0
+    };
+    if hue < 0.0 {
+        hue + 6.0
+    } else {
+        hue
+    }
+}
+
+// hsv saturation in [0, 1]
+pub fn saturation(color: Rgb) -> f64 {
+    let max = color.iter().copied().max().unwrap();
+    let min = color.iter().copied().min().unwrap();
+    if max == 0 {
+        return 0.0;
+    }
+    (max - min) as f64 / max as f64
+}
+
+// rec. 709 luma weights in integer form so sorting stays exact
+pub fn luma(color: Rgb) -> u32 {
+    2126 * color[0] as u32 + 7152 * color[1] as u32 + 722 * color[2] as u32
+}
+
+pub fn morton_index(color: Rgb) -> u32 {
+    let mut index = 0;
+    for bit in (0..8).rev() {
+        for &channel in color.iter() {
+            index = (index << 1) | ((channel as u32 >> bit) & 1);
+        }
+    }
+    index
+}
+
+pub fn hilbert_index(color: Rgb) -> u32 {
+    hilbert_index_bits([color[0] as u32, color[1] as u32, color[2] as u32], 8)
+}
+
+// position along a 3d hilbert curve through a cube of side 2^bits, using
+// skilling's transpose algorithm
+pub fn hilbert_index_bits(mut coords: [u32; 3], bits: u32) -> u32 {
+    let top = 1 << (bits - 1);
+
+    // inverse undo
+    let mut q = top;
+    while q > 1 {
+        let p = q - 1;
+        for i in 0..3 {
+            if coords[i] & q != 0 {
+                coords[0] ^= p;
+            } else {
+                let t = (coords[0] ^ coords[i]) & p;
+                coords[0] ^= t;
+                coords[i] ^= t;
+            }
+        }
+        q >>= 1;
+    }
+
+    // gray encode
+    coords[1] ^= coords[0];
+    coords[2] ^= coords[1];
+    let mut t = 0;
+    q = top;
+    while q > 1 {
+        if coords[2] & q != 0 {
+            t ^= q - 1;
+        }
+        q >>= 1;
+    }
+    for coord in coords.iter_mut() {
+        *coord ^= t;
+    }
+
+    // interleave the transposed form into a single index
+    let mut index = 0;
+    for bit in (0..bits).rev() {
+        for coord in coords.iter() {
+            index = (index << 1) | ((coord >> bit) & 1);
+        }
+    }
+    index
+}
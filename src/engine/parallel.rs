@@ -0,0 +1,80 @@
+use super::Engine;
+use std::fmt;
+use std::str::FromStr;
+
+// how an engine spreads its placement work across threads
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Parallelism {
+    // everything on the calling thread
+    Serial,
+    // one color at a time, the linear frontier scan is split across threads.
+    // places exactly the cells the serial engine places
+    Exact,
+    // searches the best cells for the next `batch` colors at once against the
+    // same frontier, then places them in color order. a color whose cell was
+    // taken earlier in its batch is searched again. much faster, but colors
+    // never see the cells opened earlier in their batch, so the image differs
+    // from a serial run and depends on the batch size
+    Batched(usize),
+}
+
+impl Parallelism {
+    pub fn check(&self) -> Result<(), String> {
+        if *self == Parallelism::Batched(0) {
+            return Err(String::from("a parallel batch needs at least one color"));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Parallelism {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Parallelism::Serial => write!(f, "serial"),
+            Parallelism::Exact => write!(f, "exact"),
+            Parallelism::Batched(batch) => write!(f, "batched={}", batch),
+        }
+    }
+}
+
+impl FromStr for Parallelism {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, String> {
+        let parallelism = match name {
+            "serial" => Parallelism::Serial,
+            "exact" => Parallelism::Exact,
+            _ => match name.strip_prefix("batched=") {
+                Some(batch) => Parallelism::Batched(
+                    batch
+                        .parse()
+                        .map_err(|_| format!("invalid parallel batch size '{}'", batch))?,
+                ),
+                None => {
+                    return Err(format!(
+                        "unknown parallelism '{}', expected one of serial, exact or \
+                         batched=<colors>",
+                        name
+                    ))
+                }
+            },
+        };
+        parallelism.check()?;
+        Ok(parallelism)
+    }
+}
+
+impl Engine {
+    pub fn parallelism(&self) -> Parallelism {
+        self.parallelism
+    }
+
+    // can be changed between steps, the next step continues the run in the
+    // new mode
+    pub fn set_parallelism(&mut self, parallelism: Parallelism) -> Result<(), String> {
+        parallelism.check()?;
+        self.parallelism = parallelism;
+        self.proposals.clear();
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+use std::collections::VecDeque;
+
+// placement rate over a sliding time window
+pub struct ProgressMeter {
+    window_ms: f64,
+    // (time in ms, placed count)
+    samples: VecDeque<(f64, usize)>,
+}
+
+impl ProgressMeter {
+    pub fn new(window_ms: f64) -> Self {
+        Self {
+            window_ms,
+            samples: VecDeque::new(),
+        }
+    }
+
+    pub fn record(&mut self, now_ms: f64, placed: usize) {
+        self.samples.push_back((now_ms, placed));
+        // keep one sample older than the window so the rate spans all of it
+        while self.samples.len() > 2 && now_ms - self.samples[1].0 >= self.window_ms {
+            self.samples.pop_front();
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.samples.clear();
+    }
+
+    pub fn placements_per_second(&self) -> f64 {
+        match (self.samples.front(), self.samples.back()) {
+            (Some(&(start_ms, start_placed)), Some(&(end_ms, end_placed))) if end_ms > start_ms => {
+                (end_placed - start_placed) as f64 * 1000.0 / (end_ms - start_ms)
+            }
+            _ => 0.0,
+        }
+    }
+}
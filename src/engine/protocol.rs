@@ -0,0 +1,340 @@
+use super::{
+    read_settings, write_settings, ByteReader, ByteWriter, DirtyTiles, Engine, Rect, Settings,
+};
+
+// messages between the main thread and a worker running the engine. every
+// message is a version byte, a tag byte and the payload, little endian like
+// checkpoints, so either side can be replaced by anything that speaks it
+pub const PROTOCOL_VERSION: u8 = 1;
+
+// main thread to worker
+#[derive(Clone, Debug, PartialEq)]
+pub enum WorkerRequest {
+    // replaces any run with a new one
+    Start(Settings),
+    Pause,
+    Resume,
+}
+
+// worker to main thread
+#[derive(Clone, Debug, PartialEq)]
+pub enum WorkerResponse {
+    Started {
+        width: usize,
+        height: usize,
+        remaining: usize,
+    },
+    Placements(PlacementBatch),
+    Error(String),
+}
+
+// cells that changed since the previous batch with their new rgba values
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PlacementBatch {
+    pub placed: usize,
+    pub remaining: usize,
+    pub deltas: Vec<(u32, [u8; 4])>,
+}
+
+impl WorkerRequest {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = ByteWriter::new();
+        writer.u8(PROTOCOL_VERSION);
+        match self {
+            WorkerRequest::Start(settings) => {
+                writer.u8(0);
+                write_settings(&mut writer, settings);
+            }
+            WorkerRequest::Pause => writer.u8(1),
+            WorkerRequest::Resume => writer.u8(2),
+        }
+        writer.into_bytes()
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, String> {
+        let mut reader = message_reader(bytes)?;
+        let request = match reader.u8()? {
+            0 => WorkerRequest::Start(read_settings(&mut reader)?),
+            1 => WorkerRequest::Pause,
+            2 => WorkerRequest::Resume,
+            tag => return Err(format!("unknown worker request tag {}", tag)),
+        };
+        finish(&reader)?;
+        Ok(request)
+    }
+}
+
+impl WorkerResponse {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = ByteWriter::new();
+        writer.u8(PROTOCOL_VERSION);
+        match self {
+            WorkerResponse::Started {
+                width,
+                height,
+                remaining,
+            } => {
+                writer.u8(0);
+                writer.usize(*width);
+                writer.usize(*height);
+                writer.usize(*remaining);
+            }
+            WorkerResponse::Placements(batch) => {
+                writer.u8(1);
+                writer.usize(batch.placed);
+                writer.usize(batch.remaining);
+                writer.usize(batch.deltas.len());
+                for (cell, rgba) in &batch.deltas {
+                    writer.u32(*cell);
+                    writer.bytes(rgba);
+                }
+            }
+            WorkerResponse::Error(message) => {
+                writer.u8(2);
+                writer.usize(message.len());
+                writer.bytes(message.as_bytes());
+            }
+        }
+        writer.into_bytes()
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, String> {
+        let mut reader = message_reader(bytes)?;
+        let response = match reader.u8()? {
+            0 => WorkerResponse::Started {
+                width: reader.usize()?,
+                height: reader.usize()?,
+                remaining: reader.usize()?,
+            },
+            1 => {
+                let placed = reader.usize()?;
+                let remaining = reader.usize()?;
+                let length = reader.length(8)?;
+                let mut deltas = Vec::with_capacity(length);
+                for _ in 0..length {
+                    let cell = reader.u32()?;
+                    let mut rgba = [0; 4];
+                    rgba.copy_from_slice(reader.bytes(4)?);
+                    deltas.push((cell, rgba));
+                }
+                WorkerResponse::Placements(PlacementBatch {
+                    placed,
+                    remaining,
+                    deltas,
+                })
+            }
+            2 => {
+                let length = reader.length(1)?;
+                let message = std::str::from_utf8(reader.bytes(length)?)
+                    .map_err(|_| String::from("worker error message is not utf-8"))?;
+                WorkerResponse::Error(message.to_string())
+            }
+            tag => return Err(format!("unknown worker response tag {}", tag)),
+        };
+        finish(&reader)?;
+        Ok(response)
+    }
+}
+
+fn message_reader(bytes: &[u8]) -> Result<ByteReader<'_>, String> {
+    let mut reader = ByteReader::new(bytes);
+    let version = reader.u8()?;
+    if version != PROTOCOL_VERSION {
+        return Err(format!(
+            "worker protocol version {} is not supported, expected {}",
+            version, PROTOCOL_VERSION
+        ));
+    }
+    Ok(reader)
+}
+
+fn finish(reader: &ByteReader) -> Result<(), String> {
+    if !reader.is_empty() {
+        return Err(String::from("worker message has trailing data"));
+    }
+    Ok(())
+}
+
+// the worker side: runs the engine on request and batches what it placed
+#[derive(Default)]
+pub struct EngineWorker {
+    engine: Option<Engine>,
+    paused: bool,
+    // placements already sent in a batch
+    sent: usize,
+}
+
+impl EngineWorker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // whether `step` has work to do
+    pub fn is_running(&self) -> bool {
+        !self.paused
+            && self
+                .engine
+                .as_ref()
+                .is_some_and(|engine| !engine.is_finished())
+    }
+
+    // answers an encoded request, pausing and resuming need no answer
+    pub fn handle(&mut self, request: &[u8]) -> Option<Vec<u8>> {
+        let response = match WorkerRequest::decode(request) {
+            Ok(WorkerRequest::Start(settings)) => match self.start(settings) {
+                Ok(response) => response,
+                Err(error) => WorkerResponse::Error(error),
+            },
+            Ok(WorkerRequest::Pause) => {
+                self.paused = true;
+                return None;
+            }
+            Ok(WorkerRequest::Resume) => {
+                self.paused = false;
+                return None;
+            }
+            Err(error) => WorkerResponse::Error(error),
+        };
+        Some(response.encode())
+    }
+
+    // places colors like `Engine::step_within` and returns them as an
+    // encoded batch, None when nothing was placed
+    pub fn step<F>(&mut self, budget_ms: f64, step_limit: usize, now_ms: F) -> Option<Vec<u8>>
+    where
+        F: FnMut() -> f64,
+    {
+        if !self.is_running() {
+            return None;
+        }
+        let engine = self.engine.as_mut()?;
+        engine.step_within(budget_ms, step_limit, now_ms);
+        let cells = &engine.recording()?.cells()[self.sent..];
+        if cells.is_empty() {
+            return None;
+        }
+        let pixels = engine.pixels();
+        let deltas = cells
+            .iter()
+            .map(|&cell| {
+                let at = cell as usize * 4;
+                let mut rgba = [0; 4];
+                rgba.copy_from_slice(&pixels[at..at + 4]);
+                (cell, rgba)
+            })
+            .collect();
+        self.sent = engine.placed();
+        let batch = PlacementBatch {
+            placed: engine.placed(),
+            remaining: engine.remaining(),
+            deltas,
+        };
+        Some(WorkerResponse::Placements(batch).encode())
+    }
+
+    fn start(&mut self, settings: Settings) -> Result<WorkerResponse, String> {
+        let mut engine = Engine::new(settings)?;
+        // the recording tells which cells to send
+        engine.start_recording()?;
+        let response = WorkerResponse::Started {
+            width: engine.width(),
+            height: engine.height(),
+            remaining: engine.remaining(),
+        };
+        self.engine = Some(engine);
+        self.paused = false;
+        self.sent = 0;
+        Ok(response)
+    }
+}
+
+// the main thread copy of the grid a worker fills
+pub struct GridMirror {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+    placed: usize,
+    remaining: usize,
+    dirty: DirtyTiles,
+}
+
+impl GridMirror {
+    // the sizes come from the worker, so they are checked like any other
+    // untrusted input before the grid is allocated
+    pub fn new(width: usize, height: usize, remaining: usize) -> Result<Self, String> {
+        let cell_count = width
+            .checked_mul(height)
+            .filter(|&cells| cells > 0 && cells <= u32::MAX as usize)
+            .ok_or_else(|| format!("the worker started an invalid {}x{} grid", width, height))?;
+        if remaining > cell_count {
+            return Err(format!(
+                "the worker started with {} colors for {} cells",
+                remaining, cell_count
+            ));
+        }
+        let pixels = cell_count
+            .checked_mul(4)
+            .map(|bytes| vec![0; bytes])
+            .ok_or_else(|| {
+                format!(
+                    "the worker started a {}x{} grid that is too large",
+                    width, height
+                )
+            })?;
+        Ok(Self {
+            width,
+            height,
+            pixels,
+            placed: 0,
+            remaining,
+            dirty: DirtyTiles::new(width, height),
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn placed(&self) -> usize {
+        self.placed
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn take_dirty_rects(&mut self) -> Vec<Rect> {
+        self.dirty.take_rects()
+    }
+
+    // a batch with a cell outside the grid is rejected as a whole
+    pub fn apply(&mut self, batch: &PlacementBatch) -> Result<(), String> {
+        let cell_count = self.width * self.height;
+        if let Some((cell, _)) = batch
+            .deltas
+            .iter()
+            .find(|&&(cell, _)| cell as usize >= cell_count)
+        {
+            return Err(format!(
+                "worker sent cell {} for the {}x{} grid",
+                cell, self.width, self.height
+            ));
+        }
+        for &(cell, rgba) in &batch.deltas {
+            let cell = cell as usize;
+            self.pixels[cell * 4..cell * 4 + 4].copy_from_slice(&rgba);
+            self.dirty.mark(cell % self.width, cell / self.width);
+        }
+        self.placed = batch.placed;
+        self.remaining = batch.remaining;
+        Ok(())
+    }
+}
@@ -0,0 +1,149 @@
+use super::{ByteReader, ByteWriter};
+
+const RECORDING_MAGIC: &[u8; 4] = b"CSRC";
+pub const RECORDING_VERSION: u16 = 1;
+
+// the cells of a run in placement order. together with the finished grid
+// this redraws the run at any number of placements
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Recording {
+    width: usize,
+    height: usize,
+    cells: Vec<u32>,
+}
+
+impl Recording {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            cells: Vec::new(),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn cells(&self) -> &[u32] {
+        &self.cells
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn push(&mut self, cell: usize) {
+        self.cells.push(cell as u32);
+    }
+
+    // the rgba grid after the first `placements` placements, taking the
+    // colors from the finished `grid`
+    pub fn frame(&self, grid: &[u8], placements: usize) -> Vec<u8> {
+        let mut frame = vec![0; grid.len()];
+        for &cell in &self.cells[..placements.min(self.len())] {
+            let cell = cell as usize;
+            frame[cell * 4..cell * 4 + 4].copy_from_slice(&grid[cell * 4..cell * 4 + 4]);
+        }
+        frame
+    }
+
+    // successive cells of a run are mostly close to each other, so each is
+    // stored as a zigzag varint of the step from the previous one
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = ByteWriter::new();
+        writer.bytes(RECORDING_MAGIC);
+        writer.u16(RECORDING_VERSION);
+        writer.usize(self.width);
+        writer.usize(self.height);
+        writer.usize(self.cells.len());
+        let mut previous = 0i64;
+        for &cell in &self.cells {
+            let delta = i64::from(cell) - previous;
+            writer.varint(((delta << 1) ^ (delta >> 63)) as u64);
+            previous = i64::from(cell);
+        }
+        writer.into_bytes()
+    }
+
+    // the grid size in the header of a recording, e.g. to match it against
+    // an image before decoding the cells
+    pub fn size_of(bytes: &[u8]) -> Result<(usize, usize), String> {
+        let mut reader = ByteReader::new(bytes);
+        let (width, height, _) = read_header(&mut reader)?;
+        Ok((width, height))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let mut reader = ByteReader::new(bytes);
+        let (width, height, cell_count) = read_header(&mut reader)?;
+        let length = reader.length(1)?;
+        if length > cell_count {
+            return Err(format!(
+                "recording of {} placements does not fit the {}x{} grid",
+                length, width, height
+            ));
+        }
+
+        let mut recording = Self::new(width, height);
+        // sized by the placements rather than by the grid the header claims
+        let mut placed = std::collections::HashSet::with_capacity(length);
+        let mut previous = 0i64;
+        for _ in 0..length {
+            let zigzag = reader.varint()?;
+            let delta = (zigzag >> 1) as i64 ^ -((zigzag & 1) as i64);
+            let cell = previous
+                .checked_add(delta)
+                .filter(|&cell| (0..cell_count as i64).contains(&cell))
+                .ok_or_else(|| {
+                    format!(
+                        "recording places outside the {}x{} grid at step {}",
+                        width,
+                        height,
+                        recording.len()
+                    )
+                })?;
+            if !placed.insert(cell) {
+                return Err(format!(
+                    "recording places cell {} twice, again at step {}",
+                    cell,
+                    recording.len()
+                ));
+            }
+            recording.push(cell as usize);
+            previous = cell;
+        }
+        if !reader.is_empty() {
+            return Err(String::from("recording has trailing data"));
+        }
+        Ok(recording)
+    }
+}
+
+// magic, version and grid size, returns the cell count as well
+fn read_header(reader: &mut ByteReader) -> Result<(usize, usize, usize), String> {
+    if reader.bytes(4).ok() != Some(&RECORDING_MAGIC[..]) {
+        return Err(String::from("not a color shredder recording"));
+    }
+    let version = reader.u16()?;
+    if version != RECORDING_VERSION {
+        return Err(format!(
+            "recording version {} is not supported, expected {}",
+            version, RECORDING_VERSION
+        ));
+    }
+    let (width, height) = (reader.usize()?, reader.usize()?);
+    let cell_count = width
+        .checked_mul(height)
+        .filter(|&count| count <= u32::MAX as usize)
+        .ok_or_else(|| format!("a {}x{} recording is too large", width, height))?;
+    Ok((width, height, cell_count))
+}
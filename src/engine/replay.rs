@@ -0,0 +1,156 @@
+use super::{DirtyTiles, Engine, Recording, Rect};
+
+// plays a recorded run back and forth over its finished grid, e.g. to
+// present a run without computing it again
+pub struct Replay {
+    recording: Recording,
+    // rgba colors of every recorded cell
+    grid: Vec<u8>,
+    pixels: Vec<u8>,
+    position: usize,
+    // placements per second, negative plays backwards
+    speed: f64,
+    // fractional position carried between ticks
+    cursor: f64,
+    clock_ms: Option<f64>,
+    dirty: DirtyTiles,
+}
+
+impl Replay {
+    // starts before the first placement and paused. `grid` has to fill
+    // exactly the recorded cells
+    pub fn new(recording: Recording, grid: Vec<u8>) -> Result<Self, String> {
+        let (width, height) = (recording.width(), recording.height());
+        if grid.len() != width * height * 4 {
+            return Err(format!(
+                "grid of {} bytes does not match the {}x{} recording",
+                grid.len(),
+                width,
+                height
+            ));
+        }
+        let filled = grid.chunks_exact(4).filter(|pixel| pixel[3] != 0).count();
+        if let Some(&cell) = recording
+            .cells()
+            .iter()
+            .find(|&&cell| grid[cell as usize * 4 + 3] == 0)
+        {
+            return Err(format!(
+                "the recording places cell {} which is empty in the grid",
+                cell
+            ));
+        }
+        if filled != recording.len() {
+            return Err(format!(
+                "the grid has {} filled cells but the recording {} placements",
+                filled,
+                recording.len()
+            ));
+        }
+
+        Ok(Self {
+            pixels: vec![0; grid.len()],
+            dirty: DirtyTiles::new(width, height),
+            recording,
+            grid,
+            position: 0,
+            speed: 0.0,
+            cursor: 0.0,
+            clock_ms: None,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.recording.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.recording.height()
+    }
+
+    pub fn len(&self) -> usize {
+        self.recording.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.recording.is_empty()
+    }
+
+    // number of placements shown
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    // the grid after `position` placements
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn take_dirty_rects(&mut self) -> Vec<Rect> {
+        self.dirty.take_rects()
+    }
+
+    pub fn mark_all_dirty(&mut self) {
+        self.dirty.mark_all();
+    }
+
+    // jumps to `position` placements, clamped to the recording. only the
+    // cells placed or removed on the way are touched
+    pub fn seek(&mut self, position: usize) {
+        let position = position.min(self.len());
+        let (from, to) = if position > self.position {
+            (self.position, position)
+        } else {
+            (position, self.position)
+        };
+        let forward = position > self.position;
+        let width = self.width();
+        for &cell in &self.recording.cells()[from..to] {
+            let cell = cell as usize;
+            let pixel = &mut self.pixels[cell * 4..cell * 4 + 4];
+            if forward {
+                pixel.copy_from_slice(&self.grid[cell * 4..cell * 4 + 4]);
+            } else {
+                pixel.copy_from_slice(&[0; 4]);
+            }
+            self.dirty.mark(cell % width, cell / width);
+        }
+        self.position = position;
+        self.cursor = position as f64;
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    // placements per second, negative plays backwards and 0 pauses
+    pub fn set_speed(&mut self, placements_per_second: f64) {
+        self.speed = placements_per_second;
+    }
+
+    // moves along at the current speed for the time passed since the last
+    // tick, returns whether the position changed. playback stops at either
+    // end of the recording
+    pub fn tick(&mut self, now_ms: f64) -> bool {
+        let elapsed_ms = match self.clock_ms.replace(now_ms) {
+            Some(last_ms) => (now_ms - last_ms).max(0.0),
+            None => 0.0,
+        };
+        let cursor = (self.cursor + self.speed * elapsed_ms / 1000.0).clamp(0.0, self.len() as f64);
+        let position = cursor as usize;
+        let moved = position != self.position;
+        self.seek(position);
+        self.cursor = cursor;
+        moved
+    }
+}
+
+impl Engine {
+    // a replay of the recorded placements so far
+    pub fn replay(&self) -> Result<Replay, String> {
+        let recording = self
+            .recording()
+            .ok_or("the run was not recorded, start recording before the first step")?;
+        Replay::new(recording.clone(), self.pixels.clone())
+    }
+}
@@ -0,0 +1,94 @@
+use super::{ColorMetric, Rgb};
+use std::fmt;
+use std::str::FromStr;
+
+// how a frontier cell is scored against the color about to be placed, lower
+// scores win
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlacementScore {
+    // distance to the closest filled neighbor
+    MinNeighbor,
+    // distance to the mean of the filled neighbors
+    AverageNeighbor,
+    // mean distance to the filled neighbors, weighted by the neighborhood
+    // weights so orthogonal neighbors count more than diagonal ones
+    WeightedNeighbor,
+}
+
+impl PlacementScore {
+    // `neighbors` yields the colors of the filled neighbors with their weights
+    pub fn score<I>(&self, metric: &dyn ColorMetric, target: &[f64; 3], neighbors: I) -> f64
+    where
+        I: Iterator<Item = (Rgb, f64)>,
+    {
+        match self {
+            PlacementScore::MinNeighbor => neighbors
+                .map(|(color, _)| metric.distance(target, &metric.embed(color)))
+                .fold(f64::INFINITY, f64::min),
+            PlacementScore::AverageNeighbor => match mean_embedding(metric, neighbors) {
+                Some(mean) => metric.distance(target, &mean),
+                None => f64::INFINITY,
+            },
+            PlacementScore::WeightedNeighbor => {
+                let (mut total, mut total_weight) = (0.0, 0.0);
+                for (color, weight) in neighbors {
+                    total += weight * metric.distance(target, &metric.embed(color));
+                    total_weight += weight;
+                }
+                if total_weight == 0.0 {
+                    return f64::INFINITY;
+                }
+                total / total_weight
+            }
+        }
+    }
+}
+
+// mean of the embedded neighbor colors, summed in iteration order so that
+// keys cached by the frontier index match freshly computed ones bit for bit
+pub fn mean_embedding<I>(metric: &dyn ColorMetric, neighbors: I) -> Option<[f64; 3]>
+where
+    I: Iterator<Item = (Rgb, f64)>,
+{
+    let mut sum = [0.0; 3];
+    let mut count = 0;
+    for (color, _) in neighbors {
+        let embedded = metric.embed(color);
+        for channel in 0..3 {
+            sum[channel] += embedded[channel];
+        }
+        count += 1;
+    }
+    if count == 0 {
+        return None;
+    }
+    let count = count as f64;
+    Some([sum[0] / count, sum[1] / count, sum[2] / count])
+}
+
+impl fmt::Display for PlacementScore {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            PlacementScore::MinNeighbor => "min",
+            PlacementScore::AverageNeighbor => "average",
+            PlacementScore::WeightedNeighbor => "weighted",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for PlacementScore {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, String> {
+        match name {
+            "min" => Ok(PlacementScore::MinNeighbor),
+            "average" => Ok(PlacementScore::AverageNeighbor),
+            "weighted" => Ok(PlacementScore::WeightedNeighbor),
+            _ => Err(format!(
+                "unknown placement score '{}', expected one of min, average or weighted",
+                name
+            )),
+        }
+    }
+}
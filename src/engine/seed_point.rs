@@ -0,0 +1,136 @@
+use super::{ColorMetric, Rgb};
+use std::fmt;
+use std::str::FromStr;
+
+// a cell the run starts growing from, placed before any other color
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SeedPoint {
+    pub x: usize,
+    pub y: usize,
+    // None takes the next color of the ordering
+    pub color: Option<Rgb>,
+}
+
+impl SeedPoint {
+    pub fn new(x: usize, y: usize) -> Self {
+        Self { x, y, color: None }
+    }
+
+    pub fn with_color(x: usize, y: usize, color: Rgb) -> Self {
+        Self {
+            x,
+            y,
+            color: Some(color),
+        }
+    }
+}
+
+pub fn check_seed_points(points: &[SeedPoint], width: usize, height: usize) -> Result<(), String> {
+    for (i, point) in points.iter().enumerate() {
+        if point.x >= width || point.y >= height {
+            return Err(format!(
+                "seed point {} at {},{} lies outside the {}x{} grid",
+                i, point.x, point.y, width, height
+            ));
+        }
+        if let Some(j) = points[..i]
+            .iter()
+            .position(|other| (other.x, other.y) == (point.x, point.y))
+        {
+            return Err(format!(
+                "seed points {} and {} both sit at {},{}",
+                j, i, point.x, point.y
+            ));
+        }
+    }
+    if points.len() > width * height {
+        return Err(format!(
+            "{} seed points do not fit into {} cells",
+            points.len(),
+            width * height
+        ));
+    }
+    Ok(())
+}
+
+// moves the colors of the seed points to the front of the ordered `colors`.
+// an explicit seed color replaces the closest remaining color under `metric`
+// so the run still places exactly one color per cell. returns the (seed,
+// replaced) pairs of explicit colors the source did not hold
+pub fn take_seed_colors(
+    points: &[SeedPoint],
+    colors: &mut Vec<Rgb>,
+    metric: &dyn ColorMetric,
+) -> Vec<(Rgb, Rgb)> {
+    let mut seed_colors = Vec::with_capacity(points.len());
+    let mut substituted = Vec::new();
+    for point in points {
+        if let Some(color) = point.color {
+            let target = metric.embed(color);
+            let closest = (0..colors.len())
+                .min_by(|&a, &b| {
+                    let distance = |i: usize| metric.distance(&target, &metric.embed(colors[i]));
+                    distance(a).total_cmp(&distance(b)).then(a.cmp(&b))
+                })
+                .unwrap();
+            let replaced = colors.remove(closest);
+            if replaced != color {
+                substituted.push((color, replaced));
+            }
+        }
+    }
+
+    let mut ordered = colors.drain(..);
+    for point in points {
+        seed_colors.push(match point.color {
+            Some(color) => color,
+            None => ordered.next().unwrap(),
+        });
+    }
+    let rest: Vec<Rgb> = ordered.collect();
+    seed_colors.extend(rest);
+    *colors = seed_colors;
+    substituted
+}
+
+// "x,y" or "x,y,#rrggbb"
+impl fmt::Display for SeedPoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)?;
+        if let Some([r, g, b]) = self.color {
+            write!(f, ",#{:02x}{:02x}{:02x}", r, g, b)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for SeedPoint {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, String> {
+        let invalid = || format!("invalid seed point '{}', expected x,y or x,y,#rrggbb", spec);
+        let parts: Vec<&str> = spec.split(',').map(str::trim).collect();
+        let (x, y, color) = match parts[..] {
+            [x, y] => (x, y, None),
+            [x, y, color] => (x, y, Some(color)),
+            _ => return Err(invalid()),
+        };
+        let x = x.parse().map_err(|_| invalid())?;
+        let y = y.parse().map_err(|_| invalid())?;
+        let color = match color {
+            Some(color) => Some(parse_hex_color(color).ok_or_else(invalid)?),
+            None => None,
+        };
+        Ok(Self { x, y, color })
+    }
+}
+
+// "#rrggbb"
+pub fn parse_hex_color(color: &str) -> Option<Rgb> {
+    let hex = color.strip_prefix('#')?;
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
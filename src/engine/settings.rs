@@ -0,0 +1,72 @@
+use super::{
+    ColorOrdering, ColorSource, Mask, Metric, Neighborhood, PlacementScore, SeedPoint, Topology,
+};
+
+// everything that decides what a run produces; equal settings always give a bit-identical image
+#[derive(Clone, Debug, PartialEq)]
+pub struct Settings {
+    pub width: usize,
+    pub height: usize,
+    pub seed: u64,
+    pub source: ColorSource,
+    pub ordering: ColorOrdering,
+    pub metric: Metric,
+    pub score: PlacementScore,
+    pub neighborhood: Neighborhood,
+    pub topology: Topology,
+    // cells placed first, in this order
+    pub seed_points: Vec<SeedPoint>,
+    // restricts which cells get filled, None fills the whole grid
+    pub mask: Option<Mask>,
+}
+
+impl Settings {
+    pub fn new(width: usize, height: usize, seed: u64) -> Self {
+        Self {
+            width,
+            height,
+            seed,
+            source: ColorSource::SampledCube {
+                count: width * height,
+            },
+            ordering: ColorOrdering::Shuffle,
+            metric: Metric::Rgb,
+            score: PlacementScore::MinNeighbor,
+            neighborhood: Neighborhood::Moore,
+            topology: Topology::Bounded,
+            seed_points: Vec::new(),
+            mask: None,
+        }
+    }
+
+    pub fn cell_count(&self) -> usize {
+        self.width * self.height
+    }
+
+    // cells the run fills, the color source has to yield exactly this many
+    pub fn placeable_cells(&self) -> usize {
+        match &self.mask {
+            Some(mask) => mask.placeable_count(),
+            None => self.cell_count(),
+        }
+    }
+
+    // the same run restricted to `mask`. a source that no longer yields
+    // exactly the placeable cells is replaced by a sample of that many cube
+    // colors and returned, so callers can tell about the substitution
+    pub fn with_mask(&self, mask: Option<Mask>) -> (Settings, Option<ColorSource>) {
+        let mut settings = Settings {
+            mask,
+            ..self.clone()
+        };
+        let placeable = settings.placeable_cells();
+        if settings.source.count() == Ok(placeable) {
+            return (settings, None);
+        }
+        let replaced = std::mem::replace(
+            &mut settings.source,
+            ColorSource::SampledCube { count: placeable },
+        );
+        (settings, Some(replaced))
+    }
+}
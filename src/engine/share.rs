@@ -0,0 +1,54 @@
+use super::{check_settings, read_settings, write_settings, ByteReader, ByteWriter, Settings};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine as _;
+
+pub const SHARE_VERSION: u8 = 1;
+// decoded strings may not ask for more, so a pasted link cannot make the page
+// allocate gigabytes
+pub const MAX_SHARED_CELLS: usize = 1 << 26;
+
+// a url safe string holding `settings`, e.g. for a link to the same run. the
+// version byte is followed by the deflated binary settings, base64url encoded
+pub fn encode_share_string(settings: &Settings) -> String {
+    let mut writer = ByteWriter::new();
+    write_settings(&mut writer, settings);
+    let mut bytes = vec![SHARE_VERSION];
+    bytes.extend(miniz_oxide::deflate::compress_to_vec(
+        &writer.into_bytes(),
+        9,
+    ));
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+// the settings of a share string, checked like any other run
+pub fn decode_share_string(text: &str) -> Result<Settings, String> {
+    let bytes = URL_SAFE_NO_PAD
+        .decode(text.trim())
+        .map_err(|_| String::from("the share string is damaged, it is not base64url"))?;
+    let (&version, deflated) = bytes.split_first().ok_or("the share string is empty")?;
+    if version != SHARE_VERSION {
+        return Err(format!(
+            "share string version {} is not supported, expected {}",
+            version, SHARE_VERSION
+        ));
+    }
+    // settings hold at most one mask byte per cell besides a small header
+    let limit = MAX_SHARED_CELLS + (1 << 16);
+    let inflated = miniz_oxide::inflate::decompress_to_vec_with_limit(deflated, limit)
+        .map_err(|_| String::from("the share string is damaged, its settings do not inflate"))?;
+
+    let mut reader = ByteReader::new(&inflated);
+    let settings = read_settings(&mut reader)
+        .map_err(|error| format!("the share string is damaged, {}", error))?;
+    if !reader.is_empty() {
+        return Err(String::from("the share string has trailing data"));
+    }
+    if settings.width.saturating_mul(settings.height) > MAX_SHARED_CELLS {
+        return Err(format!(
+            "shared runs are limited to {} cells, got {}x{}",
+            MAX_SHARED_CELLS, settings.width, settings.height
+        ));
+    }
+    check_settings(&settings)?;
+    Ok(settings)
+}
@@ -0,0 +1,157 @@
+use super::Rgb;
+use rand::Rng;
+
+const CUBE_SIZE: usize = 1 << 24;
+
+// where the multiset of colors for a run comes from
+#[derive(Clone, Debug, PartialEq)]
+pub enum ColorSource {
+    // every color at `bits` bits per channel, 2^(3 * bits) colors
+    Cube { bits: u32 },
+    // `count` distinct colors drawn uniformly from the 24 bit cube
+    SampledCube { count: usize },
+    // an explicit list, duplicates are placed as often as they appear
+    Palette(Vec<Rgb>),
+    // (color, occurrences) pairs, usually built from an image
+    Histogram(Vec<(Rgb, usize)>),
+}
+
+impl ColorSource {
+    // histogram of the opaque pixels of an rgba buffer
+    pub fn from_rgba(rgba: &[u8]) -> Result<Self, String> {
+        if !rgba.len().is_multiple_of(4) {
+            return Err(format!(
+                "image data of {} bytes is not a whole number of rgba pixels",
+                rgba.len()
+            ));
+        }
+
+        let mut counts = std::collections::BTreeMap::new();
+        for pixel in rgba.chunks_exact(4).filter(|pixel| pixel[3] != 0) {
+            *counts.entry([pixel[0], pixel[1], pixel[2]]).or_insert(0) += 1;
+        }
+        Ok(ColorSource::Histogram(counts.into_iter().collect()))
+    }
+
+    // the full color cube with exactly `cell_count` colors, if there is one
+    pub fn cube_for(cell_count: usize) -> Option<Self> {
+        (1..=8)
+            .find(|bits| 1usize << (3 * bits) == cell_count)
+            .map(|bits| ColorSource::Cube { bits })
+    }
+
+    // every cube color when the cells fit a full cube, otherwise a sample
+    pub fn filling(cell_count: usize) -> Self {
+        Self::cube_for(cell_count).unwrap_or(ColorSource::SampledCube { count: cell_count })
+    }
+
+    // the number of colors, an error when they are too many to count
+    pub fn count(&self) -> Result<usize, String> {
+        match self {
+            ColorSource::Cube { bits } => bits
+                .checked_mul(3)
+                .and_then(|shift| 1usize.checked_shl(shift))
+                .ok_or_else(|| format!("a color cube of {} bits has too many colors", bits)),
+            ColorSource::SampledCube { count } => Ok(*count),
+            ColorSource::Palette(colors) => Ok(colors.len()),
+            ColorSource::Histogram(counts) => {
+                counts
+                    .iter()
+                    .enumerate()
+                    .try_fold(0usize, |total, (i, &(_, count))| {
+                        total.checked_add(count).ok_or_else(|| {
+                            format!("histogram entry {} makes the color count too large", i)
+                        })
+                    })
+            }
+        }
+    }
+
+    pub fn check(&self, cell_count: usize) -> Result<(), String> {
+        match self {
+            ColorSource::Cube { bits } if !(1..=8).contains(bits) => {
+                return Err(format!(
+                    "a color cube needs 1 to 8 bits per channel, got {}",
+                    bits
+                ));
+            }
+            ColorSource::SampledCube { count } if *count > CUBE_SIZE => {
+                return Err(format!(
+                    "cannot sample {} distinct colors from the {} colors of the rgb cube",
+                    count, CUBE_SIZE
+                ));
+            }
+            ColorSource::Histogram(counts) => {
+                if let Some((i, &(_, count))) = counts
+                    .iter()
+                    .enumerate()
+                    .find(|(_, &(_, count))| count > cell_count)
+                {
+                    return Err(format!(
+                        "histogram entry {} holds {} colors but the grid has {} cells",
+                        i, count, cell_count
+                    ));
+                }
+            }
+            _ => {}
+        }
+
+        let count = self.count()?;
+        if count != cell_count {
+            return Err(format!(
+                "{} yields {} colors but the grid has {} cells",
+                self.describe(),
+                count,
+                cell_count
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            ColorSource::Cube { bits } => format!("the {} bit color cube", bits),
+            ColorSource::SampledCube { count } => format!("a sample of {} cube colors", count),
+            ColorSource::Palette(colors) => format!("a palette of {} entries", colors.len()),
+            ColorSource::Histogram(counts) => {
+                format!("an image histogram of {} distinct colors", counts.len())
+            }
+        }
+    }
+
+    pub fn colors<R: Rng>(&self, rng: &mut R) -> Vec<Rgb> {
+        match self {
+            ColorSource::Cube { bits } => all_colors(*bits),
+            ColorSource::SampledCube { count } => {
+                let mut indices = rand::seq::index::sample(rng, CUBE_SIZE, *count).into_vec();
+                // keep the sample in cube order like the other sources
+                indices.sort_unstable();
+                indices
+                    .into_iter()
+                    .map(|index| [(index >> 16) as u8, (index >> 8) as u8, index as u8])
+                    .collect()
+            }
+            ColorSource::Palette(colors) => colors.clone(),
+            ColorSource::Histogram(counts) => counts
+                .iter()
+                .flat_map(|&(color, count)| std::iter::repeat_n(color, count))
+                .collect(),
+        }
+    }
+}
+
+// every color representable with `bits` bits per channel, in rgb order
+pub fn all_colors(bits: u32) -> Vec<Rgb> {
+    let levels = 1u32 << bits;
+    let scale = |level: u32| (level * 255 / (levels - 1)) as u8;
+
+    let mut colors = Vec::with_capacity((levels * levels * levels) as usize);
+    for r in 0..levels {
+        for g in 0..levels {
+            for b in 0..levels {
+                colors.push([scale(r), scale(g), scale(b)]);
+            }
+        }
+    }
+    colors
+}
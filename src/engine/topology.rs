@@ -0,0 +1,115 @@
+use std::fmt;
+use std::str::FromStr;
+
+// what lies beyond the edges of the grid
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Topology {
+    Bounded,
+    // the left and right edges touch
+    WrapHorizontal,
+    // the top and bottom edges touch
+    WrapVertical,
+    Torus,
+    // neighbors past an edge are reflected back into the grid
+    Mirrored,
+}
+
+// behavior of a single axis
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Edge {
+    Bounded,
+    Wrap,
+    Mirror,
+}
+
+impl Topology {
+    // edges of the x and y axes
+    pub fn edges(self) -> (Edge, Edge) {
+        match self {
+            Topology::Bounded => (Edge::Bounded, Edge::Bounded),
+            Topology::WrapHorizontal => (Edge::Wrap, Edge::Bounded),
+            Topology::WrapVertical => (Edge::Bounded, Edge::Wrap),
+            Topology::Torus => (Edge::Wrap, Edge::Wrap),
+            Topology::Mirrored => (Edge::Mirror, Edge::Mirror),
+        }
+    }
+}
+
+impl Edge {
+    // the grid coordinate `coord` lands on along an axis of `size` cells
+    pub fn resolve(self, coord: isize, size: usize) -> Option<usize> {
+        let size = size as isize;
+        let resolved = match self {
+            Edge::Bounded => coord,
+            Edge::Wrap => coord.rem_euclid(size),
+            // reflected once around the edge cell, without repeating it
+            Edge::Mirror if coord < 0 => -coord,
+            Edge::Mirror if coord >= size => 2 * (size - 1) - coord,
+            Edge::Mirror => coord,
+        };
+        if (0..size).contains(&resolved) {
+            Some(resolved as usize)
+        } else {
+            None
+        }
+    }
+
+    // every coordinate c with `resolve(c + delta) == coord`, the inverse of
+    // stepping `delta` cells from c. may repeat coordinates on tiny grids
+    pub fn sources(self, coord: usize, delta: isize, size: usize) -> Vec<usize> {
+        let (coord, last) = (coord as isize, size as isize - 1);
+        let landings = match self {
+            Edge::Bounded | Edge::Wrap => vec![coord],
+            Edge::Mirror => vec![coord, -coord, 2 * last - coord],
+        };
+
+        let mut sources = Vec::new();
+        for landing in landings {
+            let source = match self {
+                Edge::Wrap => Some((landing - delta).rem_euclid(size as isize) as usize),
+                _ => Some(landing - delta)
+                    .filter(|source| (0..=last).contains(source))
+                    .map(|source| source as usize),
+            };
+            if let Some(source) = source {
+                if !sources.contains(&source)
+                    && self.resolve(source as isize + delta, size) == Some(coord as usize)
+                {
+                    sources.push(source);
+                }
+            }
+        }
+        sources
+    }
+}
+
+impl fmt::Display for Topology {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Topology::Bounded => "bounded",
+            Topology::WrapHorizontal => "wrap-x",
+            Topology::WrapVertical => "wrap-y",
+            Topology::Torus => "torus",
+            Topology::Mirrored => "mirror",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Topology {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, String> {
+        match name {
+            "bounded" => Ok(Topology::Bounded),
+            "wrap-x" => Ok(Topology::WrapHorizontal),
+            "wrap-y" => Ok(Topology::WrapVertical),
+            "torus" => Ok(Topology::Torus),
+            "mirror" => Ok(Topology::Mirrored),
+            _ => Err(format!(
+                "unknown topology '{}', expected one of bounded, wrap-x, wrap-y, torus or mirror",
+                name
+            )),
+        }
+    }
+}
@@ -0,0 +1,248 @@
+use super::{check_settings, run_colors, CellMask, Engine, Rgb, Settings};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::fmt;
+
+// how a grid differs from the colors of its source. a valid grid fills every
+// placeable cell and uses each source color exactly as often as the source
+// provides it, so explicit seed colors the source lacks make it invalid too
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    // colors placed more often than the source provides them, with the
+    // surplus. colors the source lacks entirely count as surplus too
+    pub duplicates: Vec<(Rgb, usize)>,
+    // colors placed less often than the source provides them, with the
+    // shortfall
+    pub missing: Vec<(Rgb, usize)>,
+    // empty cells the run should have filled
+    pub unfilled: Vec<usize>,
+    // filled cells the mask forbids
+    pub forbidden_filled: Vec<usize>,
+    // explicit seed colors the source lacks, with the source color each one
+    // was placed instead of. these are not listed as duplicate or missing
+    pub substituted: Vec<(Rgb, Rgb)>,
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.duplicates.is_empty()
+            && self.missing.is_empty()
+            && self.unfilled.is_empty()
+            && self.forbidden_filled.is_empty()
+            && self.substituted.is_empty()
+    }
+}
+
+// at most this many colors or cells are listed per problem in the summary
+const LISTED: usize = 8;
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_valid() {
+            return write!(f, "valid: every color is placed exactly once");
+        }
+
+        let mut lines = Vec::new();
+        for &(name, counts) in [("duplicate", &self.duplicates), ("missing", &self.missing)].iter()
+        {
+            if counts.is_empty() {
+                continue;
+            }
+            let total: usize = counts.iter().map(|&(_, count)| count).sum();
+            let listed: Vec<String> = counts
+                .iter()
+                .take(LISTED)
+                .map(|&(color, count)| format!("{} x{}", hex(color), count))
+                .collect();
+            lines.push(format!(
+                "{} {} colors ({} placements): {}{}",
+                counts.len(),
+                name,
+                total,
+                listed.join(", "),
+                if counts.len() > LISTED { ", ..." } else { "" }
+            ));
+        }
+        for &(name, cells) in [
+            ("unfilled", &self.unfilled),
+            ("filled forbidden", &self.forbidden_filled),
+        ]
+        .iter()
+        {
+            if cells.is_empty() {
+                continue;
+            }
+            let listed: Vec<String> = cells.iter().take(LISTED).map(usize::to_string).collect();
+            lines.push(format!(
+                "{} {} cells: {}{}",
+                cells.len(),
+                name,
+                listed.join(", "),
+                if cells.len() > LISTED { ", ..." } else { "" }
+            ));
+        }
+        if !self.substituted.is_empty() {
+            let listed: Vec<String> = self
+                .substituted
+                .iter()
+                .take(LISTED)
+                .map(|&(seed, replaced)| format!("{} for {}", hex(seed), hex(replaced)))
+                .collect();
+            lines.push(format!(
+                "{} seed colors not in the source: {}{}",
+                self.substituted.len(),
+                listed.join(", "),
+                if self.substituted.len() > LISTED {
+                    ", ..."
+                } else {
+                    ""
+                }
+            ));
+        }
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Engine {
+    // checks the grid as it is now, a run is only valid once it is finished
+    pub fn validate(&self) -> ValidationReport {
+        compare(&self.settings, &self.pixels)
+    }
+}
+
+// checks `rgba`, row by row like `Engine::pixels` with alpha 0 for empty
+// cells, against the colors a run with `settings` places
+pub fn validate_pixels(settings: &Settings, rgba: &[u8]) -> Result<ValidationReport, String> {
+    check_settings(settings)?;
+    if rgba.len() != settings.cell_count() * 4 {
+        return Err(format!(
+            "image of {} bytes does not match the {}x{} grid",
+            rgba.len(),
+            settings.width,
+            settings.height
+        ));
+    }
+    Ok(compare(settings, rgba))
+}
+
+// checks an exported or edited png against `settings`
+pub fn validate_png(settings: &Settings, png: &[u8]) -> Result<ValidationReport, String> {
+    let (width, height, rgba) = decode_png(png)?;
+    if (width, height) != (settings.width, settings.height) {
+        return Err(format!(
+            "the image is {}x{} but the grid is {}x{}",
+            width, height, settings.width, settings.height
+        ));
+    }
+    validate_pixels(settings, &rgba)
+}
+
+// width, height and rgba pixels of any 8 or 16 bit png, transparent pixels
+// are empty cells
+pub fn decode_png(png: &[u8]) -> Result<(usize, usize, Vec<u8>), String> {
+    let mut decoder = png::Decoder::new(png);
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+    let mut reader = decoder.read_info().map_err(|error| error.to_string())?;
+    let mut data = vec![0; reader.output_buffer_size()];
+    let info = reader
+        .next_frame(&mut data)
+        .map_err(|error| error.to_string())?;
+    data.truncate(info.buffer_size());
+
+    let rgba = match info.color_type {
+        png::ColorType::Rgba => data,
+        png::ColorType::Rgb => data
+            .chunks_exact(3)
+            .flat_map(|pixel| [pixel[0], pixel[1], pixel[2], 255])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => data
+            .chunks_exact(2)
+            .flat_map(|pixel| [pixel[0], pixel[0], pixel[0], pixel[1]])
+            .collect(),
+        png::ColorType::Grayscale => data
+            .iter()
+            .flat_map(|&gray| [gray, gray, gray, 255])
+            .collect(),
+        png::ColorType::Indexed => return Err(String::from("indexed png was not expanded")),
+    };
+    Ok((info.width as usize, info.height as usize, rgba))
+}
+
+fn compare(settings: &Settings, rgba: &[u8]) -> ValidationReport {
+    // the colors of the source as drawn for the run, before seed points
+    // replace any of them
+    let mut expected = settings
+        .source
+        .colors(&mut ChaCha8Rng::seed_from_u64(settings.seed));
+    let mut report = ValidationReport::default();
+    let mut placed = Vec::with_capacity(expected.len());
+    for (cell, pixel) in rgba.chunks_exact(4).enumerate() {
+        let filled = pixel[3] != 0;
+        let mask = match &settings.mask {
+            Some(mask) => mask.cell(cell),
+            None => CellMask::Allowed,
+        };
+        match (filled, mask) {
+            (true, CellMask::Forbidden) => report.forbidden_filled.push(cell),
+            (false, CellMask::Forbidden) => {}
+            (false, _) => report.unfilled.push(cell),
+            (true, _) => {}
+        }
+        if filled {
+            placed.push([pixel[0], pixel[1], pixel[2]]);
+        }
+    }
+
+    // walk both sorted lists one run of equal colors at a time
+    expected.sort_unstable();
+    placed.sort_unstable();
+    let (mut e, mut p) = (0, 0);
+    while e < expected.len() || p < placed.len() {
+        let color = match (expected.get(e), placed.get(p)) {
+            (Some(&a), Some(&b)) => a.min(b),
+            (Some(&a), None) => a,
+            (None, Some(&b)) => b,
+            (None, None) => unreachable!(),
+        };
+        let run = |colors: &[Rgb], start: usize| {
+            colors[start..]
+                .iter()
+                .take_while(|&&other| other == color)
+                .count()
+        };
+        let (wanted, got) = (run(&expected, e), run(&placed, p));
+        if got > wanted {
+            report.duplicates.push((color, got - wanted));
+        } else if wanted > got {
+            report.missing.push((color, wanted - got));
+        }
+        e += wanted;
+        p += got;
+    }
+
+    // a seed color placed instead of the color it replaced is reported as
+    // such rather than as a duplicate and a missing color
+    let (_, _, substituted) = run_colors(settings);
+    for (seed, replaced) in substituted {
+        let surplus = report
+            .duplicates
+            .iter()
+            .position(|&(color, _)| color == seed);
+        let shortfall = report
+            .missing
+            .iter()
+            .position(|&(color, _)| color == replaced);
+        if let (Some(surplus), Some(shortfall)) = (surplus, shortfall) {
+            report.duplicates[surplus].1 -= 1;
+            report.missing[shortfall].1 -= 1;
+            report.substituted.push((seed, replaced));
+        }
+    }
+    report.duplicates.retain(|&(_, count)| count > 0);
+    report.missing.retain(|&(_, count)| count > 0);
+    report
+}
+
+fn hex([r, g, b]: Rgb) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
@@ -1,34 +1,666 @@
 use wasm_bindgen::prelude::*;
 use web_sys::WebGlRenderingContext;
 
+// the webgl glue spells out field names, returns and draw call arguments
+#[allow(dead_code, clippy::needless_update, clippy::redundant_field_names)]
 mod app_state;
+#[allow(clippy::needless_borrow, clippy::needless_return)]
 mod common_functions;
+pub mod engine;
+#[allow(clippy::needless_return)]
 mod gl_setup;
+#[allow(
+    clippy::needless_borrow,
+    clippy::needless_return,
+    clippy::redundant_field_names,
+    clippy::too_many_arguments
+)]
 mod programs;
+pub mod screen;
 mod shaders;
 
+// 2^18 colors at 6 bits per channel exactly fill a 512x512 grid
+const GRID_WIDTH: usize = 512;
+const GRID_HEIGHT: usize = 512;
+const COLOR_BITS: u32 = 6;
+// placement rate is averaged over this many milliseconds
+const PROGRESS_WINDOW_MS: f64 = 1000.0;
+// the finished image of an animation stays up this long before it loops
+const ANIMATION_FINAL_DELAY_MS: u16 = 2000;
+
 #[wasm_bindgen]
 pub struct Canvas {
+    engine: engine::Engine,
+    progress: engine::ProgressMeter,
     webgl_context: WebGlRenderingContext,
     webgl_program_texture_2d: programs::Texture2D,
+    // shown instead of the engine while set
+    replay: Option<engine::Replay>,
+    // the grid of a worker running the engine, shown instead of the local one
+    mirror: Option<engine::GridMirror>,
+    // set by `use_worker`, restarts are sent to it
+    worker: Option<web_sys::Worker>,
+    // start requests the worker has not answered yet, placements of the runs
+    // they replace are dropped
+    pending_starts: usize,
 }
 
 #[wasm_bindgen]
 impl Canvas {
     #[wasm_bindgen(constructor)]
-    pub fn new() -> Self {
+    pub fn new(seed: u64) -> Self {
         console_error_panic_hook::set_once();
-        let webgl_context = gl_setup::initialize_webgl_contex().unwrap();
+        // average scoring lets the frontier index answer the best cell
+        // lookups, min neighbor scoring scans the whole frontier per color
+        let settings = engine::Settings {
+            source: engine::ColorSource::Cube { bits: COLOR_BITS },
+            score: engine::PlacementScore::AverageNeighbor,
+            ..engine::Settings::new(GRID_WIDTH, GRID_HEIGHT, seed)
+        };
+        let engine = engine::Engine::new(settings).unwrap();
+        Self::with_engine(engine)
+    }
 
-        Self {
-            webgl_program_texture_2d: programs::Texture2D::new(&webgl_context),
-            webgl_context: webgl_context,
-        }
+    // resumes a run saved with `checkpoint`
+    pub fn from_checkpoint(checkpoint: &[u8]) -> Result<Canvas, JsValue> {
+        console_error_panic_hook::set_once();
+        let engine = engine::Engine::from_checkpoint(checkpoint)
+            .map_err(|error| JsValue::from_str(&error))?;
+        Ok(Self::with_engine(engine))
+    }
+
+    // restarts with a json preset, `mask_rgba` is the image its mask field
+    // refers to, scaled to the grid
+    pub fn load_config(&mut self, json: &str, mask_rgba: Option<Vec<u8>>) -> Result<(), JsValue> {
+        let config = engine::RunConfig::from_json(json).map_err(|error| JsValue::from_str(&error))?;
+        let mask = match mask_rgba {
+            Some(rgba) => Some(
+                engine::Mask::from_alpha(config.width, config.height, &rgba)
+                    .map_err(|error| JsValue::from_str(&error))?,
+            ),
+            None => None,
+        };
+        let settings = config
+            .to_settings(mask)
+            .map_err(|error| JsValue::from_str(&error))?;
+        self.restart(settings)
+    }
+
+    // a short url safe string that restarts the identical run elsewhere
+    // a worker runs the same settings, so this also describes its run
+    pub fn share_string(&self) -> String {
+        engine::encode_share_string(self.engine.settings())
+    }
+
+    // restarts with the run of a share string, malformed strings leave the
+    // current run untouched and report why
+    pub fn load_share_string(&mut self, text: &str) -> Result<(), JsValue> {
+        let settings =
+            engine::decode_share_string(text).map_err(|error| JsValue::from_str(&error))?;
+        self.restart(settings)
+    }
+
+    // the settings of this run as a json preset, a mask is not included. a
+    // worker runs the same settings
+    pub fn config(&self) -> String {
+        engine::RunConfig::from_settings(self.engine.settings()).to_json()
+    }
+
+    // the full run state, for storing in indexeddb or downloading
+    pub fn checkpoint(&self) -> Result<Vec<u8>, JsValue> {
+        Ok(self.local_engine("save a checkpoint")?.checkpoint())
+    }
+
+    // whether the grid uses every color of the run exactly once
+    pub fn validate(&self) -> Result<Validation, JsValue> {
+        Ok(Validation {
+            report: self.local_engine("validate the run")?.validate(),
+        })
+    }
+
+    // checks an imported png against the settings of this run
+    pub fn validate_png(&self, png: &[u8]) -> Result<Validation, JsValue> {
+        let report = engine::validate_png(self.engine.settings(), png)
+            .map_err(|error| JsValue::from_str(&error))?;
+        Ok(Validation { report })
+    }
+
+    // the run so far as a looping animated png of at most `frames` frames
+    pub fn export_animation(&self, frames: usize, frame_delay_ms: u16) -> Result<Vec<u8>, JsValue> {
+        let engine = self.local_engine("export an animation")?;
+        let mut settings =
+            engine::AnimationSettings::with_frame_count(engine.placed(), frames, frame_delay_ms);
+        settings.final_delay_ms = ANIMATION_FINAL_DELAY_MS;
+        engine
+            .encode_animation(&settings)
+            .map_err(|error| JsValue::from_str(&error))
+    }
+
+    // the placement order of the run, empty for runs resumed from a
+    // checkpoint that was saved without one
+    pub fn recording(&self) -> Result<Vec<u8>, JsValue> {
+        Ok(match self.local_engine("export the recording")?.recording() {
+            Some(recording) => recording.to_bytes(),
+            None => Vec::new(),
+        })
+    }
+
+    // the grid as a png with the run settings in text chunks
+    pub fn export_png(&self) -> Result<Vec<u8>, JsValue> {
+        self.local_engine("export a png")?
+            .encode_png(true)
+            .map_err(|error| JsValue::from_str(&error))
     }
 
-    pub fn update(&mut self, time: f32, height: f32, width: f32) -> Result<(), JsValue> {
+    // places colors until `budget_ms` milliseconds have passed or `step_limit`
+    // colors were placed (0 for no limit), returns the number placed
+    pub fn update(
+        &mut self,
+        time: f32,
+        height: f32,
+        width: f32,
+        budget_ms: f64,
+        step_limit: usize,
+    ) -> Result<usize, JsValue> {
         app_state::update_dynamic_data(time, height, width);
-        return Ok(());
+
+        let performance = web_sys::window()
+            .and_then(|window| window.performance())
+            .ok_or_else(|| JsValue::from_str("performance clock is unavailable"))?;
+        if let Some(replay) = &mut self.replay {
+            replay.tick(performance.now());
+            let dirty_rects = replay.take_dirty_rects();
+            if !dirty_rects.is_empty() {
+                self.webgl_program_texture_2d.upload_rects(
+                    &self.webgl_context,
+                    replay.pixels(),
+                    &dirty_rects,
+                );
+            }
+            return Ok(0);
+        }
+        if let Some(mirror) = &mut self.mirror {
+            self.progress.record(performance.now(), mirror.placed());
+            let dirty_rects = mirror.take_dirty_rects();
+            if !dirty_rects.is_empty() {
+                self.webgl_program_texture_2d.upload_rects(
+                    &self.webgl_context,
+                    mirror.pixels(),
+                    &dirty_rects,
+                );
+            }
+            return Ok(0);
+        }
+        // waiting for the worker to start
+        if self.worker.is_some() {
+            return Ok(0);
+        }
+
+        let placed = self
+            .engine
+            .step_within(budget_ms, step_limit, || performance.now());
+        self.progress.record(performance.now(), self.engine.placed());
+
+        // only send the tiles that changed since the last frame
+        let dirty_rects = self.engine.take_dirty_rects();
+        if !dirty_rects.is_empty() {
+            self.webgl_program_texture_2d.upload_rects(
+                &self.webgl_context,
+                self.engine.pixels(),
+                &dirty_rects,
+            );
+        }
+
+        Ok(placed)
+    }
+
+    pub fn placed(&self) -> usize {
+        match &self.mirror {
+            Some(mirror) => mirror.placed(),
+            None => self.engine.placed(),
+        }
+    }
+
+    pub fn remaining(&self) -> usize {
+        match &self.mirror {
+            Some(mirror) => mirror.remaining(),
+            None => self.engine.remaining(),
+        }
+    }
+
+    // places the colors in `worker`, a web worker running a `PlacementWorker`
+    // (see worker.js) whose messages go to `apply_worker_message`. from here
+    // on the canvas only shows its grid and sends it every restart
+    pub fn use_worker(&mut self, worker: web_sys::Worker) -> Result<(), JsValue> {
+        self.replay = None;
+        self.worker = Some(worker);
+        self.start_worker()
+    }
+
+    // asks a `PlacementWorker` to run this canvas's settings, its messages go
+    // to `apply_worker_message`
+    pub fn worker_start_request(&self) -> Vec<u8> {
+        engine::WorkerRequest::Start(self.engine.settings().clone()).encode()
+    }
+
+    pub fn worker_pause_request(&self) -> Vec<u8> {
+        engine::WorkerRequest::Pause.encode()
+    }
+
+    pub fn worker_resume_request(&self) -> Vec<u8> {
+        engine::WorkerRequest::Resume.encode()
+    }
+
+    // takes over a message posted by the worker, the placements are uploaded
+    // to the texture on the next update
+    pub fn apply_worker_message(&mut self, message: &[u8]) -> Result<(), JsValue> {
+        let response =
+            engine::WorkerResponse::decode(message).map_err(|error| JsValue::from_str(&error))?;
+        match response {
+            engine::WorkerResponse::Started {
+                width,
+                height,
+                remaining,
+            } => {
+                self.pending_starts = self.pending_starts.saturating_sub(1);
+                // a later start request replaces this run
+                if self.pending_starts > 0 {
+                    return Ok(());
+                }
+                if (width, height) != (self.engine.width(), self.engine.height()) {
+                    return Err(JsValue::from_str(&format!(
+                        "the worker started a {}x{} grid for the {}x{} run",
+                        width,
+                        height,
+                        self.engine.width(),
+                        self.engine.height()
+                    )));
+                }
+                let mirror = engine::GridMirror::new(width, height, remaining)
+                    .map_err(|error| JsValue::from_str(&error))?;
+                // the texture already has the run's size, only its pixels
+                // change to the worker's
+                let grid = engine::Rect {
+                    x: 0,
+                    y: 0,
+                    width,
+                    height,
+                };
+                self.webgl_program_texture_2d.upload_rects(
+                    &self.webgl_context,
+                    mirror.pixels(),
+                    &[grid],
+                );
+                self.progress.reset();
+                self.mirror = Some(mirror);
+            }
+            // placements of a run that was restarted since
+            engine::WorkerResponse::Placements(_) if self.pending_starts > 0 => {}
+            engine::WorkerResponse::Placements(batch) => {
+                let mirror = self.mirror.as_mut().ok_or_else(|| {
+                    JsValue::from_str("the worker sent placements before it started")
+                })?;
+                mirror
+                    .apply(&batch)
+                    .map_err(|error| JsValue::from_str(&error))?;
+            }
+            // most likely a start request the worker could not run
+            engine::WorkerResponse::Error(error) => {
+                self.pending_starts = self.pending_starts.saturating_sub(1);
+                return Err(JsValue::from_str(&error));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn placements_per_second(&self) -> f64 {
+        self.progress.placements_per_second()
+    }
+    pub fn seed(&self) -> u64 {
+        self.engine.settings().seed
+    }
+
+    // the setters below restart the run with the changed settings
+
+    pub fn set_ordering(&mut self, name: &str) -> Result<(), JsValue> {
+        let ordering = name.parse().map_err(|error: String| JsValue::from_str(&error))?;
+        self.restart(engine::Settings {
+            ordering,
+            ..self.engine.settings().clone()
+        })
+    }
+
+    pub fn ordering(&self) -> String {
+        self.engine.settings().ordering.to_string()
+    }
+
+    pub fn set_metric(&mut self, name: &str) -> Result<(), JsValue> {
+        let metric = name.parse().map_err(|error: String| JsValue::from_str(&error))?;
+        self.restart(engine::Settings {
+            metric,
+            ..self.engine.settings().clone()
+        })
+    }
+
+    pub fn metric(&self) -> String {
+        self.engine.settings().metric.to_string()
+    }
+
+    pub fn set_score(&mut self, name: &str) -> Result<(), JsValue> {
+        let score = name.parse().map_err(|error: String| JsValue::from_str(&error))?;
+        self.restart(engine::Settings {
+            score,
+            ..self.engine.settings().clone()
+        })
+    }
+
+    pub fn score(&self) -> String {
+        self.engine.settings().score.to_string()
+    }
+
+    // von-neumann, moore, disk=<radius>, hex or custom=<dx,dy,weight;...>
+    pub fn set_neighborhood(&mut self, name: &str) -> Result<(), JsValue> {
+        let neighborhood = name.parse().map_err(|error: String| JsValue::from_str(&error))?;
+        self.restart(engine::Settings {
+            neighborhood,
+            ..self.engine.settings().clone()
+        })
+    }
+
+    pub fn neighborhood(&self) -> String {
+        self.engine.settings().neighborhood.to_string()
+    }
+
+    // bounded, wrap-x, wrap-y, torus or mirror
+    pub fn set_topology(&mut self, name: &str) -> Result<(), JsValue> {
+        let topology = name.parse().map_err(|error: String| JsValue::from_str(&error))?;
+        self.restart(engine::Settings {
+            topology,
+            ..self.engine.settings().clone()
+        })
+    }
+
+    pub fn topology(&self) -> String {
+        self.engine.settings().topology.to_string()
+    }
+
+    // seed points restart the run and are placed before anything else, a
+    // point outside the grid leaves the current run untouched
+    pub fn add_seed_point(&mut self, x: u32, y: u32) -> Result<(), JsValue> {
+        self.restart_with_seed_point(engine::SeedPoint::new(x as usize, y as usize))
+    }
+
+    pub fn add_colored_seed_point(
+        &mut self,
+        x: u32,
+        y: u32,
+        r: u8,
+        g: u8,
+        b: u8,
+    ) -> Result<(), JsValue> {
+        self.restart_with_seed_point(engine::SeedPoint::with_color(
+            x as usize,
+            y as usize,
+            [r, g, b],
+        ))
+    }
+
+    // `spec` is a ';' separated list of x,y or x,y,#rrggbb points
+    pub fn set_seed_points(&mut self, spec: &str) -> Result<(), JsValue> {
+        let seed_points = spec
+            .split(';')
+            .filter(|point| !point.trim().is_empty())
+            .map(str::parse)
+            .collect::<Result<Vec<engine::SeedPoint>, String>>()
+            .map_err(|error| JsValue::from_str(&error))?;
+        self.restart(engine::Settings {
+            seed_points,
+            ..self.engine.settings().clone()
+        })
+    }
+
+    // drops a seed under the pointer at canvas pixel (`x`, `y`), returns
+    // whether the pointer was over the grid
+    pub fn add_seed_point_at(&mut self, x: f32, y: f32) -> Result<bool, JsValue> {
+        match self.screen_to_grid(x, y) {
+            Some((column, row)) => {
+                self.restart_with_seed_point(engine::SeedPoint::new(column, row))?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    // seeds every cell along a dragged pointer path of x, y canvas pixel
+    // pairs, returns the number of seeds added
+    pub fn add_seed_stroke(&mut self, path: &[f32]) -> Result<usize, JsValue> {
+        let cells = self.stroke_cells(path);
+        let mut settings = self.engine.settings().clone();
+        let count = settings.seed_points.len();
+        for (column, row) in cells {
+            let taken = settings
+                .seed_points
+                .iter()
+                .any(|point| (point.x, point.y) == (column, row));
+            if !taken {
+                settings.seed_points.push(engine::SeedPoint::new(column, row));
+            }
+        }
+        let added = settings.seed_points.len() - count;
+        if added > 0 {
+            self.restart(settings)?;
+        }
+        Ok(added)
+    }
+
+    // masks restart the run, a color source that no longer fits the cells
+    // left to fill is replaced by sampled colors and the returned note says so
+
+    // alpha 0 is forbidden, 255 allowed and anything in between fill last.
+    // `rgba` must match the grid size
+    pub fn set_mask_from_alpha(&mut self, rgba: &[u8]) -> Result<Option<String>, JsValue> {
+        let mask = engine::Mask::from_alpha(self.engine.width(), self.engine.height(), rgba)
+            .map_err(|error| JsValue::from_str(&error))?;
+        self.restart_with_mask(Some(mask))
+    }
+
+    // only cells inside the polygons are filled. `coordinates` holds x, y grid
+    // positions and `vertex_counts` how many of them make up each polygon
+    pub fn set_mask_from_polygons(
+        &mut self,
+        coordinates: &[f64],
+        vertex_counts: &[u32],
+    ) -> Result<Option<String>, JsValue> {
+        let total = vertex_counts
+            .iter()
+            .try_fold(0usize, |total, &count| total.checked_add(count as usize))
+            .ok_or_else(|| JsValue::from_str("the polygons have too many vertices"))?;
+        if total.checked_mul(2) != Some(coordinates.len()) {
+            return Err(JsValue::from_str(&format!(
+                "{} polygon coordinates do not make up {} vertices",
+                coordinates.len(),
+                total
+            )));
+        }
+
+        let mut vertices = coordinates.chunks_exact(2).map(|pair| (pair[0], pair[1]));
+        let polygons: Vec<Vec<(f64, f64)>> = vertex_counts
+            .iter()
+            .map(|&count| vertices.by_ref().take(count as usize).collect())
+            .collect();
+        let mask =
+            engine::Mask::from_polygons(self.engine.width(), self.engine.height(), &polygons);
+        self.restart_with_mask(Some(mask))
+    }
+
+    // paints forbidden, allowed or fill-last cells along a dragged pointer
+    // path of x, y canvas pixel pairs with a round brush
+    pub fn paint_mask_stroke(
+        &mut self,
+        path: &[f32],
+        value: &str,
+        radius: u32,
+    ) -> Result<Option<String>, JsValue> {
+        let value: engine::CellMask = value
+            .parse()
+            .map_err(|error: String| JsValue::from_str(&error))?;
+        let (width, height) = (self.engine.width(), self.engine.height());
+        let mut mask = match &self.engine.settings().mask {
+            Some(mask) => mask.clone(),
+            None => engine::Mask::new(width, height),
+        };
+
+        if radius as usize > width.max(height) {
+            return Err(JsValue::from_str(&format!(
+                "a brush radius of {} is larger than the {}x{} grid",
+                radius, width, height
+            )));
+        }
+
+        let radius = i64::from(radius);
+        for (column, row) in self.stroke_cells(path) {
+            let (column, row) = (column as i64, row as i64);
+            // only the part of the brush on the grid
+            for y in (row - radius).max(0)..=(row + radius).min(height as i64 - 1) {
+                for x in (column - radius).max(0)..=(column + radius).min(width as i64 - 1) {
+                    let (dx, dy) = (x - column, y - row);
+                    if dx * dx + dy * dy <= radius * radius {
+                        mask.set(x as usize, y as usize, value);
+                    }
+                }
+            }
+        }
+        self.restart_with_mask(Some(mask))
+    }
+
+    pub fn clear_mask(&mut self) -> Result<Option<String>, JsValue> {
+        self.restart_with_mask(None)
+    }
+
+    pub fn clear_seed_points(&mut self) -> Result<(), JsValue> {
+        self.restart(engine::Settings {
+            seed_points: Vec::new(),
+            ..self.engine.settings().clone()
+        })
+    }
+
+    pub fn seed_points(&self) -> String {
+        let points: Vec<String> = self
+            .engine
+            .settings()
+            .seed_points
+            .iter()
+            .map(|point| point.to_string())
+            .collect();
+        points.join(";")
+    }
+
+    pub fn use_cube_colors(&mut self, bits: u32) -> Result<(), JsValue> {
+        self.restart_with_source(engine::ColorSource::Cube { bits })
+    }
+
+    pub fn use_sampled_colors(&mut self) -> Result<(), JsValue> {
+        let count = self.engine.cell_count();
+        self.restart_with_source(engine::ColorSource::SampledCube { count })
+    }
+
+    // `rgb` holds one color per three bytes
+    pub fn use_palette_colors(&mut self, rgb: &[u8]) -> Result<(), JsValue> {
+        if !rgb.len().is_multiple_of(3) {
+            return Err(JsValue::from_str(&format!(
+                "palette of {} bytes is not a whole number of rgb colors",
+                rgb.len()
+            )));
+        }
+        let palette = rgb
+            .chunks_exact(3)
+            .map(|color| [color[0], color[1], color[2]])
+            .collect();
+        self.restart_with_source(engine::ColorSource::Palette(palette))
+    }
+
+    // `rgba` is image data as returned by CanvasRenderingContext2D.getImageData
+    pub fn use_image_colors(&mut self, rgba: &[u8]) -> Result<(), JsValue> {
+        let source = engine::ColorSource::from_rgba(rgba).map_err(|error| JsValue::from_str(&error))?;
+        self.restart_with_source(source)
+    }
+
+    // shows a finished run from an exported png and its recording instead of
+    // the engine, paused before the first placement
+    pub fn load_replay(&mut self, png: &[u8], recording: &[u8]) -> Result<(), JsValue> {
+        self.local_engine("load a replay")?;
+        let (width, height, grid) =
+            engine::decode_png(png).map_err(|error| JsValue::from_str(&error))?;
+        // the header is checked first so its size cannot be made up
+        let (recording_width, recording_height) =
+            engine::Recording::size_of(recording).map_err(|error| JsValue::from_str(&error))?;
+        if (recording_width, recording_height) != (width, height) {
+            return Err(JsValue::from_str(&format!(
+                "the recording is {}x{} but the image is {}x{}",
+                recording_width, recording_height, width, height
+            )));
+        }
+        let recording =
+            engine::Recording::from_bytes(recording).map_err(|error| JsValue::from_str(&error))?;
+        let replay =
+            engine::Replay::new(recording, grid).map_err(|error| JsValue::from_str(&error))?;
+        self.show_replay(replay);
+        Ok(())
+    }
+
+    // replays the current run up to its latest placement, paused at the start
+    pub fn replay_run(&mut self) -> Result<(), JsValue> {
+        let replay = self
+            .local_engine("replay the run")?
+            .replay()
+            .map_err(|error| JsValue::from_str(&error))?;
+        self.show_replay(replay);
+        Ok(())
+    }
+
+    // goes back to showing the engine, which continues where it was
+    pub fn stop_replay(&mut self) {
+        if self.replay.take().is_some() {
+            self.webgl_program_texture_2d.resize(
+                &self.webgl_context,
+                self.engine.width(),
+                self.engine.height(),
+                self.engine.pixels(),
+            );
+            // already uploaded with the resized texture
+            self.engine.take_dirty_rects();
+        }
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        self.replay.is_some()
+    }
+
+    pub fn replay_length(&self) -> usize {
+        self.replay.as_ref().map_or(0, |replay| replay.len())
+    }
+
+    pub fn replay_position(&self) -> usize {
+        self.replay.as_ref().map_or(0, |replay| replay.position())
+    }
+
+    // jumps to the grid after `position` placements, the changed texels are
+    // uploaded on the next update
+    pub fn seek(&mut self, position: usize) {
+        if let Some(replay) = &mut self.replay {
+            replay.seek(position);
+        }
+    }
+
+    pub fn playback_speed(&self) -> f64 {
+        self.replay.as_ref().map_or(0.0, |replay| replay.speed())
+    }
+
+    // placements per second, negative plays backwards and 0 pauses
+    pub fn set_playback_speed(&mut self, placements_per_second: f64) {
+        if let Some(replay) = &mut self.replay {
+            replay.set_speed(placements_per_second);
+        }
     }
 
     pub fn render(&self) {
@@ -49,3 +681,220 @@ impl Canvas {
         );
     }
 }
+
+impl Canvas {
+    fn with_engine(mut engine: engine::Engine) -> Self {
+        // new runs are always recorded so they can be exported as animations
+        if engine.placed() == 0 {
+            engine.start_recording().unwrap();
+        }
+        let webgl_context = gl_setup::initialize_webgl_contex().unwrap();
+        let webgl_program_texture_2d = programs::Texture2D::new(
+            &webgl_context,
+            engine.width(),
+            engine.height(),
+            engine.pixels(),
+        );
+
+        Self {
+            engine,
+            progress: engine::ProgressMeter::new(PROGRESS_WINDOW_MS),
+            webgl_program_texture_2d,
+            webgl_context,
+            replay: None,
+            mirror: None,
+            worker: None,
+            pending_starts: 0,
+        }
+    }
+
+    fn restart(&mut self, settings: engine::Settings) -> Result<(), JsValue> {
+        self.engine = engine::Engine::new(settings).map_err(|error| JsValue::from_str(&error))?;
+        self.engine.start_recording().unwrap();
+        self.replay = None;
+        self.mirror = None;
+        self.progress.reset();
+        self.webgl_program_texture_2d.resize(
+            &self.webgl_context,
+            self.engine.width(),
+            self.engine.height(),
+            self.engine.pixels(),
+        );
+        self.start_worker()
+    }
+
+    // asks the worker, if there is one, to run the current settings
+    fn start_worker(&mut self) -> Result<(), JsValue> {
+        if let Some(worker) = &self.worker {
+            let request = engine::WorkerRequest::Start(self.engine.settings().clone()).encode();
+            worker.post_message(&js_sys::Uint8Array::from(&request[..]))?;
+            self.pending_starts += 1;
+            self.mirror = None;
+        }
+        Ok(())
+    }
+
+    // the local engine, unless a worker places the colors and it sits idle
+    fn local_engine(&self, action: &str) -> Result<&engine::Engine, JsValue> {
+        if self.worker.is_some() || self.mirror.is_some() {
+            return Err(JsValue::from_str(&format!(
+                "cannot {} while a worker places the colors",
+                action
+            )));
+        }
+        Ok(&self.engine)
+    }
+
+    fn show_replay(&mut self, replay: engine::Replay) {
+        self.webgl_program_texture_2d.resize(
+            &self.webgl_context,
+            replay.width(),
+            replay.height(),
+            replay.pixels(),
+        );
+        self.replay = Some(replay);
+    }
+
+    fn screen_to_grid(&self, x: f32, y: f32) -> Option<(usize, usize)> {
+        let current_state = app_state::get_current_state();
+        let area = screen::DisplayArea {
+            bottom: current_state.control_bottom,
+            top: current_state.control_top,
+            left: current_state.control_left,
+            right: current_state.control_right,
+            canvas_height: current_state.canvas_height,
+            canvas_width: current_state.canvas_width,
+        };
+        screen::screen_to_grid(&area, x, y, self.engine.width(), self.engine.height())
+    }
+
+    // grid cells along a path of x, y canvas pixel pairs, segments leaving
+    // the grid are skipped
+    fn stroke_cells(&self, path: &[f32]) -> Vec<(usize, usize)> {
+        let mut cells = Vec::new();
+        let mut previous = None;
+        for point in path.chunks_exact(2) {
+            let current = self.screen_to_grid(point[0], point[1]);
+            match (previous, current) {
+                (Some(from), Some(to)) => {
+                    cells.extend(screen::grid_line(from, to).into_iter().skip(1))
+                }
+                (None, Some(to)) => cells.push(to),
+                _ => {}
+            }
+            previous = current;
+        }
+        cells
+    }
+
+    fn restart_with_mask(
+        &mut self,
+        mask: Option<engine::Mask>,
+    ) -> Result<Option<String>, JsValue> {
+        let (settings, replaced) = self.engine.settings().with_mask(mask);
+        let note = replaced.map(|source| {
+            format!(
+                "the mask leaves {} cells to fill, {} was replaced by {}",
+                settings.placeable_cells(),
+                source.describe(),
+                settings.source.describe()
+            )
+        });
+        self.restart(settings)?;
+        Ok(note)
+    }
+
+    fn restart_with_seed_point(&mut self, point: engine::SeedPoint) -> Result<(), JsValue> {
+        let mut settings = self.engine.settings().clone();
+        settings.seed_points.push(point);
+        self.restart(settings)
+    }
+
+    fn restart_with_source(&mut self, source: engine::ColorSource) -> Result<(), JsValue> {
+        self.restart(engine::Settings {
+            source,
+            ..self.engine.settings().clone()
+        })
+    }
+}
+
+// the outcome of `Canvas::validate`, counts are of distinct colors or cells
+#[wasm_bindgen]
+pub struct Validation {
+    report: engine::ValidationReport,
+}
+
+#[wasm_bindgen]
+impl Validation {
+    pub fn is_valid(&self) -> bool {
+        self.report.is_valid()
+    }
+
+    pub fn duplicate_count(&self) -> usize {
+        self.report.duplicates.len()
+    }
+
+    pub fn missing_count(&self) -> usize {
+        self.report.missing.len()
+    }
+
+    pub fn unfilled_count(&self) -> usize {
+        self.report.unfilled.len()
+    }
+
+    pub fn forbidden_filled_count(&self) -> usize {
+        self.report.forbidden_filled.len()
+    }
+
+    // explicit seed colors placed instead of a source color
+    pub fn substituted_count(&self) -> usize {
+        self.report.substituted.len()
+    }
+
+    // a readable summary listing the first few problems
+    pub fn summary(&self) -> String {
+        self.report.to_string()
+    }
+}
+
+// runs the engine inside a web worker, see worker.js. requests and responses
+// are the byte messages of `engine::WorkerRequest` and `engine::WorkerResponse`
+#[wasm_bindgen]
+pub struct PlacementWorker {
+    worker: engine::EngineWorker,
+}
+
+impl Default for PlacementWorker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen]
+impl PlacementWorker {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        console_error_panic_hook::set_once();
+        Self {
+            worker: engine::EngineWorker::new(),
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.worker.is_running()
+    }
+
+    // the response to post back, empty when there is none
+    pub fn handle(&mut self, request: &[u8]) -> Vec<u8> {
+        self.worker.handle(request).unwrap_or_default()
+    }
+
+    // places colors for `budget_ms` milliseconds, returns the batch to post
+    // back or an empty array when nothing was placed
+    pub fn step(&mut self, budget_ms: f64) -> Vec<u8> {
+        self
+            .worker
+            .step(budget_ms, 0, js_sys::Date::now)
+            .unwrap_or_default()
+    }
+}
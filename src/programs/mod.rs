@@ -1,6 +1,8 @@
 mod texture_2d;
 pub use texture_2d::*;
 mod color_2d;
+#[allow(unused_imports)]
 pub use color_2d::*;
 mod color_2d_gradient;
+#[allow(unused_imports)]
 pub use color_2d_gradient::*;
@@ -1,4 +1,7 @@
 use super::super::common_functions;
+use super::super::engine::Rect;
+use super::super::screen;
+use super::super::screen::DisplayArea;
 use js_sys::WebAssembly;
 use wasm_bindgen::JsCast;
 use web_sys::WebGlBuffer;
@@ -10,32 +13,42 @@ use web_sys::WebGlUniformLocation;
 pub struct Texture2D {
     program: WebGlProgram,
     rectangle_texture: WebGlTexture,
+    texture_width: usize,
     rectangle_texcoord_buffer: WebGlBuffer,
-    rectangle_color_buffer: WebGlBuffer,
     rectangle_vertex_buffer: WebGlBuffer,
     rectangle_index_count: i32,
+    a_position: u32,
+    a_texcoord: u32,
     u_opacity: WebGlUniformLocation,
     u_transform: WebGlUniformLocation,
 }
 
 impl Texture2D {
-    pub fn new(webgl_context: &WebGlRenderingContext) -> Self {
+    // `pixels` is the rgba grid the texture starts out with, row 0 on top
+    pub fn new(
+        webgl_context: &WebGlRenderingContext,
+        texture_width: usize,
+        texture_height: usize,
+        pixels: &[u8],
+    ) -> Self {
         // create program
         let program = common_functions::link_program(
-            &webgl_context,
+            webgl_context,
             super::super::shaders::vertex::texture_2d::SHADER,
             super::super::shaders::fragment::texture_2d::SHADER,
         )
         .unwrap();
 
         // create & fill rectangle vertex buffer
-        let rectangle_vertex_buffer: web_sys::WebGlBuffer = new_vertex_buffer(&webgl_context);
+        let rectangle_vertex_buffer: web_sys::WebGlBuffer = new_vertex_buffer(webgl_context);
         // create & fill rectangle index buffer
-        let rectangle_index_count: i32 = new_index_buffer(&webgl_context);
-        // create rectangle color buffer (not filled)
-        let rectangle_color_buffer: web_sys::WebGlBuffer = new_color_buffer(&webgl_context);
+        let rectangle_index_count: i32 = new_index_buffer(webgl_context);
         //create & fill texture coordinate buffer
-        let rectangle_texcoord_buffer: web_sys::WebGlBuffer = new_texcoord_buffer(&webgl_context);
+        let rectangle_texcoord_buffer: web_sys::WebGlBuffer = new_texcoord_buffer(webgl_context);
+
+        // get attribute locations
+        let a_position = webgl_context.get_attrib_location(&program, "aPosition") as u32;
+        let a_texcoord = webgl_context.get_attrib_location(&program, "aTexCoord") as u32;
 
         // get uniform pointers
         let u_opacity = webgl_context
@@ -45,20 +58,26 @@ impl Texture2D {
             .get_uniform_location(&program, "uTransform")
             .unwrap();
 
-        let rectangle_texture: WebGlTexture = webgl_context.create_texture().unwrap();
+        // create texture & upload the full grid once
+        let rectangle_texture: WebGlTexture =
+            new_grid_texture(webgl_context, texture_width, texture_height, pixels);
 
         // instantiate
         Self {
+            // attributes
+            a_position: a_position,
+            a_texcoord: a_texcoord,
+
             // uniforms
             u_opacity: u_opacity,
             u_transform: u_transform,
 
             // textures
             rectangle_texture: rectangle_texture,
+            texture_width: texture_width,
 
             // buffers
             rectangle_vertex_buffer: rectangle_vertex_buffer,
-            rectangle_color_buffer: rectangle_color_buffer,
             rectangle_texcoord_buffer: rectangle_texcoord_buffer,
 
             // counts
@@ -88,14 +107,14 @@ impl Texture2D {
             Some(&self.rectangle_vertex_buffer),
         );
         webgl_context.vertex_attrib_pointer_with_i32(
-            0,
+            self.a_position,
             2,
             WebGlRenderingContext::FLOAT,
             false,
             0,
             0,
         );
-        webgl_context.enable_vertex_attrib_array(0);
+        webgl_context.enable_vertex_attrib_array(self.a_position);
 
         // set attributes for and enable rectangle texcoord buffer
         webgl_context.bind_buffer(
@@ -103,60 +122,33 @@ impl Texture2D {
             Some(&self.rectangle_texcoord_buffer),
         );
         webgl_context.vertex_attrib_pointer_with_i32(
-            1,
+            self.a_texcoord,
             2,
             WebGlRenderingContext::FLOAT,
             false,
             0,
             0,
         );
-        webgl_context.enable_vertex_attrib_array(1);
-
-        // set attributes for and enable rectangle color buffer
-        webgl_context.bind_buffer(
-            WebGlRenderingContext::ARRAY_BUFFER,
-            Some(&self.rectangle_color_buffer),
-        );
-        webgl_context.vertex_attrib_pointer_with_i32(
-            2,
-            4,
-            WebGlRenderingContext::FLOAT,
-            false,
-            0,
-            0,
-        );
-        webgl_context.enable_vertex_attrib_array(2);
+        webgl_context.enable_vertex_attrib_array(self.a_texcoord);
 
+        // bind grid texture
         webgl_context.bind_texture(
             WebGlRenderingContext::TEXTURE_2D,
             Some(&self.rectangle_texture),
         );
-        let temp_tex_array: [u8; 4] = [0,0,255,255];
-        webgl_context
-            .tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_array_buffer_view(
-                WebGlRenderingContext::TEXTURE_2D,
-                0,
-                WebGlRenderingContext::RGBA as i32,
-                1,
-                1,
-                0,
-                WebGlRenderingContext::RGBA,
-                WebGlRenderingContext::UNSIGNED_BYTE,
-                broken,
-            );
 
         // send opacity uniform
-        webgl_context.uniform1f(Some(&self.u_opacity), 0.5);
+        webgl_context.uniform1f(Some(&self.u_opacity), 1.0);
 
         // create transform matrix uniform
-        let transform_mat = get_transform_from_canvas_dimensions(
-            bottom,
-            top,
-            left,
-            right,
-            canvas_height,
-            canvas_width,
-        );
+        let transform_mat = screen::get_transform_from_canvas_dimensions(&DisplayArea {
+            bottom: bottom,
+            top: top,
+            left: left,
+            right: right,
+            canvas_height: canvas_height,
+            canvas_width: canvas_width,
+        });
 
         // send transform matrix uniform
         webgl_context.uniform_matrix4fv_with_f32_array(
@@ -173,6 +165,97 @@ impl Texture2D {
             0,
         );
     }
+
+    // reallocates the texture for a grid of another size and fills it with
+    // `pixels`, the program and buffers are kept
+    pub fn resize(
+        &mut self,
+        webgl_context: &WebGlRenderingContext,
+        texture_width: usize,
+        texture_height: usize,
+        pixels: &[u8],
+    ) {
+        webgl_context.delete_texture(Some(&self.rectangle_texture));
+        self.rectangle_texture =
+            new_grid_texture(webgl_context, texture_width, texture_height, pixels);
+        self.texture_width = texture_width;
+    }
+
+    // stream the given rectangles of the rgba grid into the texture
+    pub fn upload_rects(&self, webgl_context: &WebGlRenderingContext, pixels: &[u8], rects: &[Rect]) {
+        webgl_context.bind_texture(
+            WebGlRenderingContext::TEXTURE_2D,
+            Some(&self.rectangle_texture),
+        );
+
+        let row_bytes = self.texture_width * 4;
+        let mut region: Vec<u8> = Vec::new();
+        for rect in rects {
+            let region_pixels = if rect.x == 0 && rect.width == self.texture_width {
+                // full rows are already contiguous
+                &pixels[rect.y * row_bytes..(rect.y + rect.height) * row_bytes]
+            } else {
+                region.clear();
+                for row in rect.y..rect.y + rect.height {
+                    let row_start = row * row_bytes + rect.x * 4;
+                    region.extend_from_slice(&pixels[row_start..row_start + rect.width * 4]);
+                }
+                &region[..]
+            };
+
+            webgl_context
+                .tex_sub_image_2d_with_i32_and_i32_and_u32_and_type_and_opt_u8_array(
+                    WebGlRenderingContext::TEXTURE_2D,
+                    0,
+                    rect.x as i32,
+                    rect.y as i32,
+                    rect.width as i32,
+                    rect.height as i32,
+                    WebGlRenderingContext::RGBA,
+                    WebGlRenderingContext::UNSIGNED_BYTE,
+                    Some(region_pixels),
+                )
+                .unwrap();
+        }
+    }
+}
+
+fn new_grid_texture(
+    webgl_context: &WebGlRenderingContext,
+    texture_width: usize,
+    texture_height: usize,
+    pixels: &[u8],
+) -> WebGlTexture {
+    // create texture
+    let texture = webgl_context.create_texture().unwrap();
+    // bind texture
+    webgl_context.bind_texture(WebGlRenderingContext::TEXTURE_2D, Some(&texture));
+    // nearest filtering keeps every grid cell a crisp pixel, clamping allows
+    // non power of two sizes
+    for &(parameter, value) in &[
+        (WebGlRenderingContext::TEXTURE_MIN_FILTER, WebGlRenderingContext::NEAREST),
+        (WebGlRenderingContext::TEXTURE_MAG_FILTER, WebGlRenderingContext::NEAREST),
+        (WebGlRenderingContext::TEXTURE_WRAP_S, WebGlRenderingContext::CLAMP_TO_EDGE),
+        (WebGlRenderingContext::TEXTURE_WRAP_T, WebGlRenderingContext::CLAMP_TO_EDGE),
+    ] {
+        webgl_context.tex_parameteri(WebGlRenderingContext::TEXTURE_2D, parameter, value as i32);
+    }
+    // fill texture
+    webgl_context
+        .tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+            WebGlRenderingContext::TEXTURE_2D,
+            0,
+            WebGlRenderingContext::RGBA as i32,
+            texture_width as i32,
+            texture_height as i32,
+            0,
+            WebGlRenderingContext::RGBA,
+            WebGlRenderingContext::UNSIGNED_BYTE,
+            Some(pixels),
+        )
+        .unwrap();
+
+    return texture;
 }
 
 fn new_vertex_buffer(webgl_context: &WebGlRenderingContext) -> web_sys::WebGlBuffer {
@@ -216,14 +299,12 @@ fn new_vertex_buffer(webgl_context: &WebGlRenderingContext) -> web_sys::WebGlBuf
 }
 
 fn new_texcoord_buffer(webgl_context: &WebGlRenderingContext) -> web_sys::WebGlBuffer {
-    // define rectangle triangle vertex indicies
-    let rectangle_texcoord_array: [f32; 12] = [
-        0.0, 1.0, // x, y
-        0.0, 0.0, // x, y
-        1.0, 1.0, // x, y
-        0.0, 0.0, // x, y
-        0.0, 1.0, // x, y
-        1.0, 1.0, // x, y
+    // define texture coordinates per rectangle vertex, texture row 0 is the top
+    let rectangle_texcoord_array: [f32; 8] = [
+        0.0, 0.0, // s, t
+        0.0, 1.0, // s, t
+        1.0, 0.0, // s, t
+        1.0, 1.0, // s, t
     ];
     // allocate memory buffer
     let texcoord_memory_buffer = wasm_bindgen::memory()
@@ -289,67 +370,3 @@ fn new_index_buffer(webgl_context: &WebGlRenderingContext) -> i32 {
 
     return rectangle_index_count;
 }
-
-fn new_color_buffer(webgl_context: &WebGlRenderingContext) -> web_sys::WebGlBuffer {
-    // define rectangle triangle vertex indicies
-    let colors: [f32; 16] = [
-        1.0, 0.0, 0.0, 1.0, //rgba
-        0.0, 1.0, 0.0, 1.0, //rgba
-        0.0, 0.0, 1.0, 1.0, //rgba
-        1.0, 1.0, 1.0, 1.0, //rgba
-    ];
-    // allocate memory buffer
-    let colors_memory_buffer = wasm_bindgen::memory()
-        .dyn_into::<WebAssembly::Memory>()
-        .unwrap()
-        .buffer();
-    // get pointer to color array
-    let color_vals_location = colors.as_ptr() as u32 / 4;
-    // put color array into web_gl format
-    let color_vals_array = js_sys::Float32Array::new(&colors_memory_buffer).subarray(
-        color_vals_location,
-        color_vals_location + colors.len() as u32,
-    );
-    // create webgl buffer
-    let rectangle_color_buffer = webgl_context
-        .create_buffer()
-        .ok_or("failed to create buffer")
-        .unwrap();
-
-    // bind buffer
-    webgl_context.bind_buffer(
-        WebGlRenderingContext::ARRAY_BUFFER,
-        Some(&rectangle_color_buffer),
-    );
-    // fill buffer
-    webgl_context.buffer_data_with_array_buffer_view(
-        WebGlRenderingContext::ARRAY_BUFFER,
-        &color_vals_array,
-        WebGlRenderingContext::DYNAMIC_DRAW,
-    );
-
-    return rectangle_color_buffer;
-}
-
-fn get_transform_from_canvas_dimensions(
-    bottom: f32,
-    top: f32,
-    left: f32,
-    right: f32,
-    canvas_height: f32,
-    canvas_width: f32,
-) -> [f32; 16] {
-    let translation_mat = common_functions::translation_matrix(
-        2.0 * left / canvas_width - 1.0,
-        2.0 * bottom / canvas_height - 1.0,
-        0.0,
-    );
-    let scale_mat = common_functions::scaling_matrix(
-        2.0 * (right - left) / canvas_width,
-        2.0 * (top - bottom) / canvas_height,
-        0.0,
-    );
-    let transform_mat = common_functions::mult_matrix_4(scale_mat, translation_mat);
-
-    return transform_mat;
-}
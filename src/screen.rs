@@ -0,0 +1,85 @@
+use super::common_functions;
+
+// the square the grid is drawn into, in canvas pixels with y growing upwards
+// like `AppState::control_*`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DisplayArea {
+    pub bottom: f32,
+    pub top: f32,
+    pub left: f32,
+    pub right: f32,
+    pub canvas_height: f32,
+    pub canvas_width: f32,
+}
+
+// maps the unit square of the grid quad into clip space
+#[allow(clippy::needless_return)]
+pub fn get_transform_from_canvas_dimensions(area: &DisplayArea) -> [f32; 16] {
+    let translation_mat = common_functions::translation_matrix(
+        2.0 * area.left / area.canvas_width - 1.0,
+        2.0 * area.bottom / area.canvas_height - 1.0,
+        0.0,
+    );
+    let scale_mat = common_functions::scaling_matrix(
+        2.0 * (area.right - area.left) / area.canvas_width,
+        2.0 * (area.top - area.bottom) / area.canvas_height,
+        0.0,
+    );
+    let transform_mat = common_functions::mult_matrix_4(scale_mat, translation_mat);
+
+    return transform_mat;
+}
+
+// the grid cell under a pointer at (`x`, `y`) canvas pixels from the top left
+// corner, None outside the display area. inverts the transform above
+pub fn screen_to_grid(
+    area: &DisplayArea,
+    x: f32,
+    y: f32,
+    grid_width: usize,
+    grid_height: usize,
+) -> Option<(usize, usize)> {
+    let transform = get_transform_from_canvas_dimensions(area);
+
+    // canvas pixels to clip space
+    let clip_x = 2.0 * x / area.canvas_width - 1.0;
+    let clip_y = 1.0 - 2.0 * y / area.canvas_height;
+    // undo scale and translation to get the position on the quad
+    let quad_x = (clip_x - transform[12]) / transform[0];
+    let quad_y = (clip_y - transform[13]) / transform[5];
+    if !(0.0..1.0).contains(&quad_x) || !(0.0..1.0).contains(&quad_y) {
+        return None;
+    }
+
+    // texture row 0 is drawn at the top of the quad
+    let column = (quad_x * grid_width as f32) as usize;
+    let row = ((1.0 - quad_y) * grid_height as f32) as usize;
+    Some((column.min(grid_width - 1), row.min(grid_height - 1)))
+}
+
+// the cells on a straight line between two cells, both ends included
+pub fn grid_line(from: (usize, usize), to: (usize, usize)) -> Vec<(usize, usize)> {
+    let (mut x, mut y) = (from.0 as isize, from.1 as isize);
+    let (end_x, end_y) = (to.0 as isize, to.1 as isize);
+    let (dx, dy) = ((end_x - x).abs(), -(end_y - y).abs());
+    let (step_x, step_y) = ((end_x - x).signum(), (end_y - y).signum());
+
+    // bresenham
+    let mut cells = Vec::new();
+    let mut error = dx + dy;
+    loop {
+        cells.push((x as usize, y as usize));
+        if (x, y) == (end_x, end_y) {
+            return cells;
+        }
+        let doubled = 2 * error;
+        if doubled >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if doubled <= dx {
+            error += dx;
+            y += step_y;
+        }
+    }
+}
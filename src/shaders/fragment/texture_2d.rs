@@ -6,6 +6,7 @@ pub const SHADER: &str = r#"
     varying vec2 v_texcoord;
     
     uniform float uOpacity;
+    uniform sampler2D u_texture;
 
     void main() {
         gl_FragColor = texture2D(u_texture, v_texcoord) * uOpacity;
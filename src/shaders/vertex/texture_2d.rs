@@ -4,6 +4,8 @@ pub const SHADER: &str = r#"
     attribute vec2 aTexCoord;
 
     uniform mat4 uTransform;
+
+    varying vec2 v_texcoord;
     
     void main() {
         // Multiply the position by the matrix.
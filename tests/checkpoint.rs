@@ -0,0 +1,145 @@
+use color_shredder::engine::{
+    CellMask, ColorOrdering, ColorSource, Engine, Mask, Metric, Neighborhood, PlacementScore,
+    SeedPoint, Settings, Topology, CHECKPOINT_VERSION,
+};
+
+const WIDTH: usize = 20;
+const HEIGHT: usize = 16;
+
+// walls that enclose growth so the run has to jump to random cells, plus a
+// fill last corner
+fn mask() -> Mask {
+    let mut mask = Mask::new(WIDTH, HEIGHT);
+    for y in 0..HEIGHT {
+        mask.set(7, y, CellMask::Forbidden);
+        mask.set(13, y, CellMask::Forbidden);
+    }
+    for y in 0..4 {
+        for x in 0..4 {
+            mask.set(x, y, CellMask::FillLast);
+        }
+    }
+    mask
+}
+
+fn variants() -> Vec<Settings> {
+    let base = Settings::new(WIDTH, HEIGHT, 11);
+    let mask = mask();
+    vec![
+        base.clone(),
+        Settings {
+            score: PlacementScore::AverageNeighbor,
+            metric: Metric::Oklab,
+            ordering: ColorOrdering::Hue,
+            ..base.clone()
+        },
+        Settings {
+            score: PlacementScore::WeightedNeighbor,
+            metric: Metric::Ciede2000,
+            neighborhood: Neighborhood::Hexagonal,
+            topology: Topology::Torus,
+            ordering: ColorOrdering::RandomHueBias { strength: 0.5 },
+            ..base.clone()
+        },
+        Settings {
+            source: ColorSource::SampledCube {
+                count: mask.placeable_count(),
+            },
+            score: PlacementScore::AverageNeighbor,
+            neighborhood: Neighborhood::Custom(vec![(1, 0, 1.0), (0, 1, 0.5), (-2, -1, 0.25)]),
+            seed_points: vec![
+                SeedPoint::new(3, 10),
+                SeedPoint::with_color(16, 2, [250, 10, 90]),
+            ],
+            mask: Some(mask),
+            ..base
+        },
+    ]
+}
+
+#[test]
+fn resumed_runs_finish_with_the_uninterrupted_image() {
+    for settings in variants() {
+        let mut uninterrupted = Engine::new(settings.clone()).unwrap();
+        uninterrupted.run();
+
+        let placeable = settings.placeable_cells();
+        for &stop in &[0, 1, placeable / 3, placeable - 1, placeable] {
+            let mut engine = Engine::new(settings.clone()).unwrap();
+            engine.step_n(stop);
+
+            let mut resumed = Engine::from_checkpoint(&engine.checkpoint()).unwrap();
+            assert_eq!(resumed.settings(), &settings);
+            assert_eq!(resumed.placed(), stop);
+            assert_eq!(resumed.pixels(), engine.pixels());
+            assert_eq!(resumed.uses_frontier_index(), engine.uses_frontier_index());
+
+            resumed.run();
+            assert_eq!(
+                resumed.pixels(),
+                uninterrupted.pixels(),
+                "{:?} stopped after {}",
+                settings,
+                stop
+            );
+        }
+    }
+}
+
+#[test]
+fn checkpoints_round_trip_byte_for_byte() {
+    for settings in variants() {
+        let mut engine = Engine::new(settings).unwrap();
+        engine.step_n(100);
+        let checkpoint = engine.checkpoint();
+        let resumed = Engine::from_checkpoint(&checkpoint).unwrap();
+        assert_eq!(resumed.checkpoint(), checkpoint);
+    }
+}
+
+#[test]
+fn resumed_engines_redraw_the_whole_grid() {
+    let mut engine = Engine::new(Settings::new(WIDTH, HEIGHT, 3)).unwrap();
+    engine.step_n(50);
+    let mut resumed = Engine::from_checkpoint(&engine.checkpoint()).unwrap();
+    let area: usize = resumed
+        .take_dirty_rects()
+        .iter()
+        .map(|rect| rect.width * rect.height)
+        .sum();
+    assert_eq!(area, WIDTH * HEIGHT);
+}
+
+#[test]
+fn rejects_damaged_checkpoints() {
+    let mut engine = Engine::new(Settings::new(WIDTH, HEIGHT, 5)).unwrap();
+    engine.step_n(40);
+    let checkpoint = engine.checkpoint();
+
+    assert_eq!(
+        Engine::from_checkpoint(b"PNG\x00").err().unwrap(),
+        "not a color shredder checkpoint"
+    );
+
+    let mut newer = checkpoint.clone();
+    newer[4..6].copy_from_slice(&(CHECKPOINT_VERSION + 1).to_le_bytes());
+    assert_eq!(
+        Engine::from_checkpoint(&newer).err().unwrap(),
+        format!(
+            "checkpoint version {} is not supported, expected {}",
+            CHECKPOINT_VERSION + 1,
+            CHECKPOINT_VERSION
+        )
+    );
+
+    for length in (0..checkpoint.len()).step_by(7) {
+        assert!(Engine::from_checkpoint(&checkpoint[..length]).is_err());
+    }
+
+    let mut trailing = checkpoint;
+    trailing.push(0);
+    assert_eq!(
+        Engine::from_checkpoint(&trailing).err().unwrap(),
+        "checkpoint has trailing data"
+    );
+}
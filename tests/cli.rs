@@ -0,0 +1,196 @@
+use color_shredder::engine::{
+    decode_png, CellMask, ColorSource, Engine, Mask, Metric, Neighborhood, PlacementScore,
+    SeedPoint, Settings,
+};
+use std::process::Command;
+
+fn shredder(args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_shredder"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn renders_the_same_image_as_the_engine() {
+    let path = std::env::temp_dir().join("color_shredder_cli_render.png");
+    let output = shredder(&[
+        "render",
+        "--width",
+        "16",
+        "--height",
+        "24",
+        "--seed",
+        "9",
+        "--metric",
+        "oklab",
+        "--neighborhood",
+        "disk=2",
+        "--seeds",
+        "3,4;10,20,#00ff00",
+        "--output",
+        path.to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+    let progress = String::from_utf8(output.stderr).unwrap();
+    assert!(progress.contains("placed 384 colors in"), "{}", progress);
+
+    let mut engine = Engine::new(Settings {
+        metric: Metric::Oklab,
+        score: PlacementScore::AverageNeighbor,
+        neighborhood: Neighborhood::Disk { radius: 2 },
+        seed_points: vec![
+            SeedPoint::new(3, 4),
+            SeedPoint::with_color(10, 20, [0, 255, 0]),
+        ],
+        ..Settings::new(16, 24, 9)
+    })
+    .unwrap();
+    engine.run();
+    let png = std::fs::read(&path).unwrap();
+    let (width, height, rgba) = decode_png(&png).unwrap();
+    assert_eq!((width, height), (16, 24));
+    assert_eq!(rgba, engine.pixels());
+
+    let validation = shredder(&[
+        "validate",
+        path.to_str().unwrap(),
+        "--sample",
+        "384",
+        "--seed",
+        "9",
+    ]);
+    // the green seed point replaced a sampled color
+    assert_eq!(validation.status.code(), Some(1));
+
+    // the run settings in the image name the seed point, so the substitution
+    // is reported like the engine reports it
+    let validation = shredder(&["validate", path.to_str().unwrap()]);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(validation.status.code(), Some(1));
+    let report = String::from_utf8(validation.stdout).unwrap();
+    assert!(
+        report.ends_with(&format!("{}\n", engine.validate())),
+        "{}",
+        report
+    );
+    assert!(
+        report.contains("1 seed colors not in the source: #00ff00 for #"),
+        "{}",
+        report
+    );
+}
+
+#[test]
+fn rejects_bad_arguments() {
+    let output = shredder(&["render", "--output", "unused.png", "--metric", "bogus"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .starts_with("unknown color metric 'bogus'"));
+
+    assert_eq!(shredder(&["render", "--width", "8"]).status.code(), Some(2));
+    assert_eq!(shredder(&["paint"]).status.code(), Some(2));
+}
+
+#[test]
+fn renders_presets_with_their_mask() {
+    let directory = std::env::temp_dir().join("color_shredder_cli_preset");
+    std::fs::create_dir_all(&directory).unwrap();
+    let preset = directory.join("preset.toml");
+    std::fs::write(
+        &preset,
+        "version = 1\nwidth = 12\nheight = 10\nseed = 4\nmetric = \"redmean\"\nmask = \"mask.png\"\n",
+    )
+    .unwrap();
+    // a transparent left column
+    let mut mask = Mask::new(12, 10);
+    for y in 0..10 {
+        mask.set(0, y, CellMask::Forbidden);
+    }
+    let alpha: Vec<u8> = (0..120)
+        .flat_map(|cell| {
+            let alpha = if cell % 12 == 0 { 0 } else { 255 };
+            vec![0, 0, 0, alpha]
+        })
+        .collect();
+    let mut mask_png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut mask_png, 12, 10);
+    encoder.set_color(png::ColorType::Rgba);
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(&alpha).unwrap();
+    writer.finish().unwrap();
+    std::fs::write(directory.join("mask.png"), mask_png).unwrap();
+
+    let output_path = directory.join("render.png");
+    let output = shredder(&[
+        "render",
+        "--config",
+        preset.to_str().unwrap(),
+        "--seed",
+        "5",
+        "-o",
+        output_path.to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let mut engine = Engine::new(Settings {
+        source: ColorSource::SampledCube { count: 110 },
+        metric: Metric::Redmean,
+        mask: Some(mask),
+        ..Settings::new(12, 10, 5)
+    })
+    .unwrap();
+    engine.run();
+    let (_, _, rgba) = decode_png(&std::fs::read(&output_path).unwrap()).unwrap();
+    assert_eq!(rgba, engine.pixels());
+
+    std::fs::write(&preset, "version = 1\nwidth = 12\nheight = 10\nshape = 3\n").unwrap();
+    let output = shredder(&[
+        "render",
+        "--config",
+        preset.to_str().unwrap(),
+        "-o",
+        output_path.to_str().unwrap(),
+    ]);
+    assert_eq!(output.status.code(), Some(2));
+    let error = String::from_utf8(output.stderr).unwrap();
+    assert!(error.contains("preset.toml: shape: "), "{}", error);
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn renders_in_parallel_with_the_feature() {
+    let path = std::env::temp_dir().join("color_shredder_cli_parallel.png");
+    let output = shredder(&[
+        "render",
+        "--width",
+        "20",
+        "--height",
+        "20",
+        "--seed",
+        "4",
+        "--score",
+        "min",
+        "--parallel",
+        "exact",
+        "--output",
+        path.to_str().unwrap(),
+    ]);
+    if !cfg!(feature = "parallel") {
+        assert_eq!(output.status.code(), Some(2));
+        let error = String::from_utf8(output.stderr).unwrap();
+        assert!(error.contains("the parallel feature"), "{}", error);
+        return;
+    }
+    assert!(output.status.success(), "{:?}", output);
+
+    let mut engine = Engine::new(Settings {
+        score: PlacementScore::MinNeighbor,
+        ..Settings::new(20, 20, 4)
+    })
+    .unwrap();
+    engine.run();
+    let (_, _, rgba) = decode_png(&std::fs::read(&path).unwrap()).unwrap();
+    assert_eq!(rgba, engine.pixels());
+}
@@ -0,0 +1,159 @@
+use color_shredder::engine::{
+    CellMask, ColorOrdering, ColorSource, Engine, HexColor, Mask, Metric, Neighborhood,
+    PlacementScore, RunConfig, SeedPoint, Settings, SourceConfig, Topology, CONFIG_VERSION,
+};
+
+fn settings() -> Settings {
+    Settings {
+        source: ColorSource::Histogram(vec![([255, 0, 0], 100), ([0, 0, 255], 92)]),
+        ordering: ColorOrdering::RandomHueBias { strength: 0.25 },
+        metric: Metric::Oklab,
+        score: PlacementScore::WeightedNeighbor,
+        neighborhood: Neighborhood::Custom(vec![(1, 0, 1.0), (0, -2, 0.5)]),
+        topology: Topology::Torus,
+        seed_points: vec![SeedPoint::new(1, 2), SeedPoint::with_color(5, 7, [1, 2, 3])],
+        ..Settings::new(16, 12, 77)
+    }
+}
+
+#[test]
+fn round_trips_settings_through_json_and_toml() {
+    let config = RunConfig::from_settings(&settings());
+    assert_eq!(config.version, CONFIG_VERSION);
+
+    let json = config.to_json();
+    assert_eq!(RunConfig::from_json(&json).unwrap(), config);
+    let toml = config.to_toml();
+    assert_eq!(RunConfig::from_toml(&toml).unwrap(), config);
+    assert_eq!(config.to_settings(None).unwrap(), settings());
+}
+
+#[test]
+fn reads_handwritten_presets() {
+    let json = r##"{
+        "version": 1,
+        "width": 8,
+        "height": 8,
+        "seed": 3,
+        "ordering": "hue",
+        "neighborhood": "disk=2",
+        "seeds": ["4,4,#ff8800"]
+    }"##;
+    let config = RunConfig::from_json(json).unwrap();
+    let settings = config.to_settings(None).unwrap();
+    assert_eq!(settings.source, ColorSource::Cube { bits: 2 });
+    assert_eq!(settings.ordering, ColorOrdering::Hue);
+    assert_eq!(settings.metric, Settings::new(8, 8, 3).metric);
+    assert_eq!(settings.neighborhood, Neighborhood::Disk { radius: 2 });
+    assert_eq!(
+        settings.seed_points,
+        vec![SeedPoint::with_color(4, 4, [255, 136, 0])]
+    );
+    assert!(Engine::new(settings).is_ok());
+
+    let toml = r##"
+        version = 1
+        width = 10
+        height = 6
+        metric = "ciede2000"
+
+        [source]
+        palette = ["#000000", "#ffffff"]
+    "##;
+    let config = RunConfig::from_toml(toml).unwrap();
+    assert_eq!(config.metric, Some(Metric::Ciede2000));
+    assert_eq!(
+        config.source,
+        Some(SourceConfig::Palette(vec![
+            HexColor([0, 0, 0]),
+            HexColor([255, 255, 255])
+        ]))
+    );
+    // the palette does not fill the grid
+    assert!(Engine::new(config.to_settings(None).unwrap()).is_err());
+}
+
+#[test]
+fn reports_errors_with_the_path_of_the_field() {
+    let cases = [
+        (
+            r#"{"version": 1, "width": 8, "height": 8, "colour": "red"}"#,
+            "colour: unknown field `colour`",
+        ),
+        (
+            r#"{"version": 1, "width": 8, "height": 8, "metric": "cie94"}"#,
+            "metric: unknown color metric 'cie94'",
+        ),
+        (
+            r#"{"version": 1, "width": 8, "height": 8, "source": {"cube": {"bits": 9}}}"#,
+            "source: a color cube needs 1 to 8 bits per channel, got 9",
+        ),
+        (
+            r#"{"version": 1, "width": 8, "height": 8, "source": {"cube": {"bits": "six"}}}"#,
+            "source.cube.bits: invalid type: string \"six\"",
+        ),
+        (
+            r##"{"version": 1, "width": 8, "height": 8, "source": {"palette": ["#fff"]}}"##,
+            "source.palette[0]: invalid color '#fff', expected #rrggbb",
+        ),
+        (
+            r#"{"version": 1, "width": 8, "height": 8, "seeds": ["1,1", "8,0"]}"#,
+            "seeds: seed point 1 at 8,0 lies outside the 8x8 grid",
+        ),
+        (
+            r#"{"version": 1, "width": 8, "height": 8, "seeds": ["1,1", "x"]}"#,
+            "seeds[1]: invalid seed point 'x'",
+        ),
+        (
+            r#"{"version": 1, "width": 8, "height": 8, "neighborhood": "disk=20"}"#,
+            "neighborhood: ",
+        ),
+        (
+            r#"{"version": 1, "width": 8, "height": 8, "ordering": "hue-bias=2"}"#,
+            "ordering: hue bias strength must be within 0 and 1, got 2",
+        ),
+        (
+            r#"{"version": 1, "width": 0, "height": 8}"#,
+            "width: must be at least 1",
+        ),
+        (r#"{"version": 1, "height": 8}"#, "missing field `width`"),
+        (
+            r#"{"version": 2, "width": 8, "height": 8, "palette": "new"}"#,
+            "version: presets of version 2 are not supported, this release reads 1 to 1",
+        ),
+        (r#"{"width": 8}"#, "missing field `version`"),
+        (r#"{"version": 1,"#, "EOF while parsing"),
+        (
+            r##"{"version": 1, "width": 1, "height": 1, "source": {"histogram":
+                [["#010203", 18446744073709551615], ["#040506", 2]]}}"##,
+            "source: histogram entry 1 makes the color count too large",
+        ),
+    ];
+    for (json, expected) in cases.iter() {
+        let error = RunConfig::from_json(json).err().unwrap();
+        assert!(error.starts_with(expected), "{} gave {}", json, error);
+    }
+
+    let error = RunConfig::from_toml("version = 1\nwidth = 8\nheight = 8\nscore = 3\n")
+        .err()
+        .unwrap();
+    assert!(error.starts_with("score: "), "{}", error);
+}
+
+#[test]
+fn needs_the_mask_it_refers_to() {
+    let json = r#"{"version": 1, "width": 6, "height": 6, "mask": "heart.png"}"#;
+    let config = RunConfig::from_json(json).unwrap();
+    assert_eq!(
+        config.to_settings(None).err().unwrap(),
+        "mask: the preset refers to 'heart.png' but no mask was loaded"
+    );
+
+    let mut mask = Mask::new(6, 6);
+    mask.set(0, 0, CellMask::Forbidden);
+    let settings = config.to_settings(Some(mask.clone())).unwrap();
+    // the default source fills the placeable cells
+    assert_eq!(settings.source, ColorSource::SampledCube { count: 35 });
+    assert_eq!(settings.mask, Some(mask));
+    assert!(Engine::new(settings).is_ok());
+}
@@ -0,0 +1,101 @@
+use color_shredder::engine::{Engine, Rect, Settings, DIRTY_TILE_SIZE};
+
+// grid that does not divide into whole tiles
+const WIDTH: usize = 150;
+const HEIGHT: usize = 70;
+
+fn engine(seed: u64) -> Engine {
+    Engine::new(Settings::new(WIDTH, HEIGHT, seed)).unwrap()
+}
+
+fn covers(rects: &[Rect], cell: usize) -> bool {
+    let (x, y) = (cell % WIDTH, cell / WIDTH);
+    rects.iter().any(|rect| {
+        (rect.x..rect.x + rect.width).contains(&x) && (rect.y..rect.y + rect.height).contains(&y)
+    })
+}
+
+#[test]
+fn starts_clean() {
+    let mut engine = engine(0);
+    assert!(!engine.has_dirty_rects());
+    assert_eq!(engine.take_dirty_rects(), vec![]);
+}
+
+#[test]
+fn single_placement_dirties_its_tile() {
+    let mut engine = engine(1);
+    let placement = engine.step().unwrap();
+    let (x, y) = (placement.cell % WIDTH, placement.cell / WIDTH);
+    let (tile_x, tile_y) = (
+        x / DIRTY_TILE_SIZE * DIRTY_TILE_SIZE,
+        y / DIRTY_TILE_SIZE * DIRTY_TILE_SIZE,
+    );
+
+    assert_eq!(
+        engine.take_dirty_rects(),
+        vec![Rect {
+            x: tile_x,
+            y: tile_y,
+            width: DIRTY_TILE_SIZE.min(WIDTH - tile_x),
+            height: DIRTY_TILE_SIZE.min(HEIGHT - tile_y),
+        }]
+    );
+    assert!(!engine.has_dirty_rects());
+    assert_eq!(engine.take_dirty_rects(), vec![]);
+}
+
+#[test]
+fn rects_cover_placements_since_the_last_drain() {
+    let mut engine = engine(2);
+    for _ in 0..20 {
+        let placements: Vec<_> = (0..150).filter_map(|_| engine.step()).collect();
+        let rects = engine.take_dirty_rects();
+
+        for placement in &placements {
+            assert!(covers(&rects, placement.cell));
+        }
+        // every rect holds at least one of the new placements
+        for rect in &rects {
+            assert!(placements
+                .iter()
+                .any(|placement| covers(&[*rect], placement.cell)));
+        }
+    }
+}
+
+#[test]
+fn adjacent_tiles_merge_into_rows() {
+    let mut engine = engine(3);
+    engine.run();
+
+    let rects = engine.take_dirty_rects();
+    assert_eq!(
+        rects,
+        vec![
+            Rect {
+                x: 0,
+                y: 0,
+                width: WIDTH,
+                height: DIRTY_TILE_SIZE,
+            },
+            Rect {
+                x: 0,
+                y: DIRTY_TILE_SIZE,
+                width: WIDTH,
+                height: HEIGHT - DIRTY_TILE_SIZE,
+            },
+        ]
+    );
+}
+
+#[test]
+fn mark_all_dirty_covers_the_grid() {
+    let mut engine = engine(4);
+    engine.mark_all_dirty();
+
+    let rects = engine.take_dirty_rects();
+    let area: usize = rects.iter().map(|rect| rect.width * rect.height).sum();
+    assert_eq!(area, WIDTH * HEIGHT);
+    assert!((0..WIDTH * HEIGHT).all(|cell| covers(&rects, cell)));
+}
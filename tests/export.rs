@@ -0,0 +1,132 @@
+use color_shredder::engine::{
+    encode_share_string, png_run_settings, CellMask, ColorSource, Engine, Mask, Metric,
+    Neighborhood, SeedPoint, Settings, Topology, RUN_KEYWORD,
+};
+
+fn decode(bytes: &[u8]) -> (png::OutputInfo, Vec<u8>, Vec<(String, String)>) {
+    let mut reader = png::Decoder::new(bytes).read_info().unwrap();
+    let mut data = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut data).unwrap();
+    data.truncate(info.buffer_size());
+    reader.finish().unwrap();
+    let text = reader
+        .info()
+        .uncompressed_latin1_text
+        .iter()
+        .map(|chunk| (chunk.keyword.clone(), chunk.text.clone()))
+        .collect();
+    (info, data, text)
+}
+
+#[test]
+fn finished_grids_round_trip_every_pixel_as_rgb() {
+    let settings = Settings {
+        metric: Metric::Oklab,
+        neighborhood: Neighborhood::Hexagonal,
+        topology: Topology::Torus,
+        ..Settings::new(24, 18, 7)
+    };
+    let mut engine = Engine::new(settings).unwrap();
+    engine.run();
+
+    let (info, data, _) = decode(&engine.encode_png(false).unwrap());
+    assert_eq!((info.width, info.height), (24, 18));
+    assert_eq!(info.color_type, png::ColorType::Rgb);
+    assert_eq!(info.bit_depth, png::BitDepth::Eight);
+    for (cell, pixel) in data.chunks_exact(3).enumerate() {
+        assert_eq!(
+            Some([pixel[0], pixel[1], pixel[2]]),
+            engine.cell_color(cell)
+        );
+    }
+}
+
+#[test]
+fn unfinished_grids_keep_empty_cells_transparent() {
+    let mut mask = Mask::new(16, 16);
+    for y in 0..16 {
+        mask.set(8, y, CellMask::Forbidden);
+    }
+    let settings = Settings {
+        source: ColorSource::SampledCube {
+            count: mask.placeable_count(),
+        },
+        mask: Some(mask),
+        ..Settings::new(16, 16, 3)
+    };
+    let mut engine = Engine::new(settings).unwrap();
+    engine.step_n(100);
+
+    let (info, data, _) = decode(&engine.encode_png(false).unwrap());
+    assert_eq!(info.color_type, png::ColorType::Rgba);
+    assert_eq!(data, engine.pixels());
+
+    engine.run();
+    let (info, data, _) = decode(&engine.encode_png(false).unwrap());
+    assert_eq!(info.color_type, png::ColorType::Rgba);
+    assert_eq!(data, engine.pixels());
+}
+
+#[test]
+fn writes_the_run_settings_as_text_chunks() {
+    let settings = Settings {
+        metric: Metric::Ciede2000,
+        neighborhood: Neighborhood::Disk { radius: 2 },
+        ..Settings::new(8, 8, 42)
+    };
+    let mut engine = Engine::new(settings.clone()).unwrap();
+    engine.run();
+
+    let (_, _, text) = decode(&engine.encode_png(true).unwrap());
+    let value = |keyword: &str| {
+        text.iter()
+            .find(|(key, _)| key == keyword)
+            .map(|(_, value)| value.clone())
+            .unwrap()
+    };
+    assert_eq!(
+        value("Software"),
+        format!("color_shredder {}", env!("CARGO_PKG_VERSION"))
+    );
+    assert_eq!(value("seed"), "42");
+    assert_eq!(value("metric"), "ciede2000");
+    assert_eq!(value("ordering"), settings.ordering.to_string());
+    assert_eq!(value("neighborhood"), "disk=2");
+    assert_eq!(value("topology"), "bounded");
+    assert_eq!(value(RUN_KEYWORD), encode_share_string(&settings));
+
+    let (_, _, text) = decode(&engine.encode_png(false).unwrap());
+    assert!(text.is_empty());
+}
+
+#[test]
+fn reproduces_the_run_from_its_png() {
+    let mut mask = Mask::new(12, 10);
+    mask.set(3, 3, CellMask::Forbidden);
+    mask.set(11, 9, CellMask::FillLast);
+    let settings = Settings {
+        source: ColorSource::SampledCube { count: 119 },
+        seed_points: vec![
+            SeedPoint::with_color(5, 5, [0, 255, 0]),
+            SeedPoint::new(1, 8),
+        ],
+        topology: Topology::Torus,
+        mask: Some(mask),
+        ..Settings::new(12, 10, 5)
+    };
+    let mut engine = Engine::new(settings.clone()).unwrap();
+    engine.run();
+    let png = engine.encode_png(true).unwrap();
+    let restored = png_run_settings(&png).unwrap().unwrap();
+    assert_eq!(restored, settings);
+
+    let mut again = Engine::new(restored).unwrap();
+    again.run();
+    assert_eq!(again.pixels(), engine.pixels());
+
+    assert_eq!(
+        png_run_settings(&engine.encode_png(false).unwrap()),
+        Ok(None)
+    );
+    assert!(png_run_settings(b"not a png").is_err());
+}
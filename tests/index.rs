@@ -0,0 +1,124 @@
+use color_shredder::engine::{
+    Candidate, ColorMetric, Engine, FrontierIndex, Metric, PlacementScore, RgbEuclidean, Settings,
+};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+fn average_settings(metric: Metric, seed: u64) -> Settings {
+    Settings {
+        metric,
+        score: PlacementScore::AverageNeighbor,
+        ..Settings::new(24, 24, seed)
+    }
+}
+
+fn placements(mut engine: Engine) -> Vec<usize> {
+    std::iter::from_fn(|| engine.step())
+        .map(|placement| placement.cell)
+        .collect()
+}
+
+#[test]
+fn index_and_linear_scan_place_identically() {
+    for &metric in &[
+        Metric::Rgb,
+        Metric::LumaWeighted,
+        Metric::Cie76,
+        Metric::Oklab,
+    ] {
+        for seed in 0..2 {
+            let indexed = Engine::new(average_settings(metric, seed)).unwrap();
+            assert!(indexed.uses_frontier_index());
+
+            let mut linear = Engine::new(average_settings(metric, seed)).unwrap();
+            linear.set_frontier_index(false);
+            assert!(!linear.uses_frontier_index());
+
+            assert_eq!(
+                placements(indexed),
+                placements(linear),
+                "{} seed {}",
+                metric,
+                seed
+            );
+        }
+    }
+}
+
+#[test]
+fn index_can_be_enabled_mid_run() {
+    let mut engine = Engine::new(average_settings(Metric::Oklab, 9)).unwrap();
+    engine.set_frontier_index(false);
+    let mut switched: Vec<usize> = (0..200).map(|_| engine.step().unwrap().cell).collect();
+    engine.set_frontier_index(true);
+    assert!(engine.uses_frontier_index());
+    switched.extend(placements(engine));
+
+    let full = placements(Engine::new(average_settings(Metric::Oklab, 9)).unwrap());
+    assert_eq!(switched, full);
+}
+
+#[test]
+fn index_is_only_used_where_it_is_exact() {
+    let min = Settings {
+        score: PlacementScore::MinNeighbor,
+        ..average_settings(Metric::Rgb, 0)
+    };
+    let ciede = average_settings(Metric::Ciede2000, 0);
+    for settings in [min, ciede] {
+        let mut engine = Engine::new(settings).unwrap();
+        engine.set_frontier_index(true);
+        assert!(!engine.uses_frontier_index());
+    }
+}
+
+#[test]
+fn nearest_matches_brute_force() {
+    let mut rng = ChaCha8Rng::seed_from_u64(3);
+    let mut index = FrontierIndex::new(&RgbEuclidean, 500);
+    let mut keys: Vec<Option<[f64; 3]>> = vec![None; 500];
+
+    for round in 0..2000 {
+        let cell = rng.gen_range(0..500usize);
+        if rng.gen_bool(0.3) {
+            assert_eq!(index.remove(cell), keys[cell].is_some());
+            keys[cell] = None;
+        } else {
+            // coarse keys so that exact ties are common
+            let key = [
+                rng.gen_range(0..8) as f64 * 32.0,
+                rng.gen_range(0..8) as f64 * 32.0,
+                rng.gen_range(0..8) as f64 * 32.0,
+            ];
+            index.insert(cell, key);
+            keys[cell] = Some(key);
+        }
+        assert_eq!(index.len(), keys.iter().flatten().count());
+
+        let target = [
+            rng.gen_range(0.0..255.0),
+            rng.gen_range(0.0..255.0),
+            rng.gen_range(0.0..255.0),
+        ];
+        let rank = |cell: usize| (cell as u64 * 7919) % 13;
+        let mut expected: Option<Candidate> = None;
+        for (cell, key) in keys.iter().enumerate() {
+            if let Some(key) = key {
+                let candidate = Candidate {
+                    score: RgbEuclidean.distance(&target, key),
+                    rank: rank(cell),
+                    cell,
+                };
+                if expected.is_none_or(|best| candidate.beats(&best)) {
+                    expected = Some(candidate);
+                }
+            }
+        }
+        assert_eq!(
+            index.nearest(&target, rank).map(|found| found.cell),
+            expected.map(|found| found.cell),
+            "round {}",
+            round
+        );
+    }
+}
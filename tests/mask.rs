@@ -0,0 +1,234 @@
+use color_shredder::engine::{
+    CellMask, ColorSource, Engine, Mask, Neighborhood, PlacementScore, SeedPoint, Settings,
+};
+
+const SIZE: usize = 12;
+
+fn masked_engine(mask: Mask, seed: u64) -> Engine {
+    let settings = Settings {
+        source: ColorSource::SampledCube {
+            count: mask.placeable_count(),
+        },
+        mask: Some(mask),
+        ..Settings::new(SIZE, SIZE, seed)
+    };
+    Engine::new(settings).unwrap()
+}
+
+// a forbidden wall down the middle with a fill last band along the top
+fn walled_mask() -> Mask {
+    let mut mask = Mask::new(SIZE, SIZE);
+    for y in 0..SIZE {
+        mask.set(SIZE / 2, y, CellMask::Forbidden);
+    }
+    for x in 0..SIZE {
+        if mask.get(x, 0) == CellMask::Allowed {
+            mask.set(x, 0, CellMask::FillLast);
+        }
+    }
+    mask
+}
+
+#[test]
+fn never_grows_into_forbidden_cells() {
+    let mask = walled_mask();
+    for seed in 0..4 {
+        let mut engine = masked_engine(mask.clone(), seed);
+        while engine.step().is_some() {
+            for cell in engine.frontier().iter() {
+                assert_ne!(mask.cell(cell), CellMask::Forbidden);
+            }
+        }
+
+        assert!(engine.is_finished());
+        assert_eq!(engine.placed(), SIZE * SIZE - SIZE);
+        for cell in 0..SIZE * SIZE {
+            let forbidden = mask.cell(cell) == CellMask::Forbidden;
+            assert_eq!(
+                engine.cell_color(cell).is_none(),
+                forbidden,
+                "cell {}",
+                cell
+            );
+        }
+    }
+}
+
+#[test]
+fn fills_last_cells_after_every_allowed_cell() {
+    let mask = walled_mask();
+    let allowed = mask.count(CellMask::Allowed);
+    for seed in 0..4 {
+        let mut engine = masked_engine(mask.clone(), seed);
+        let placements: Vec<usize> = std::iter::from_fn(|| engine.step())
+            .map(|placement| placement.cell)
+            .collect();
+        let (first, last) = placements.split_at(allowed);
+        assert!(first
+            .iter()
+            .all(|&cell| mask.cell(cell) == CellMask::Allowed));
+        assert!(last
+            .iter()
+            .all(|&cell| mask.cell(cell) == CellMask::FillLast));
+        assert_eq!(last.len(), mask.count(CellMask::FillLast));
+    }
+}
+
+#[test]
+fn keeps_fill_last_cells_out_of_the_frontier_until_released() {
+    let mask = walled_mask();
+    let allowed = mask.count(CellMask::Allowed);
+    let mut engine = masked_engine(mask.clone(), 9);
+    for _ in 0..allowed {
+        engine.step().unwrap();
+        let released = engine.placed() == allowed;
+        for cell in engine.frontier().iter() {
+            assert!(released || mask.cell(cell) == CellMask::Allowed);
+        }
+    }
+    engine.run();
+    assert!(engine.is_finished());
+}
+
+#[test]
+fn wide_kernels_do_not_jump_forbidden_cells_into_the_frontier() {
+    let mask = walled_mask();
+    let settings = Settings {
+        source: ColorSource::SampledCube {
+            count: mask.placeable_count(),
+        },
+        neighborhood: Neighborhood::Disk { radius: 3 },
+        mask: Some(mask.clone()),
+        ..Settings::new(SIZE, SIZE, 2)
+    };
+    let mut engine = Engine::new(settings).unwrap();
+    while engine.step().is_some() {
+        assert!(engine
+            .frontier()
+            .iter()
+            .all(|cell| mask.cell(cell) != CellMask::Forbidden));
+    }
+    assert_eq!(engine.placed(), mask.placeable_count());
+}
+
+#[test]
+fn color_count_has_to_match_the_placeable_cells() {
+    let settings = Settings {
+        mask: Some(walled_mask()),
+        ..Settings::new(SIZE, SIZE, 0)
+    };
+    assert_eq!(
+        Engine::new(settings).err().unwrap(),
+        "a sample of 144 cube colors yields 144 colors but the grid has 132 cells"
+    );
+}
+
+#[test]
+fn masking_replaces_sources_that_no_longer_fit() {
+    let mask = walled_mask();
+    let palette: Vec<_> = (0..mask.placeable_count() as u8)
+        .map(|i| [i, 0, 0])
+        .collect();
+    let settings = Settings {
+        source: ColorSource::Palette(palette),
+        ..Settings::new(SIZE, SIZE, 0)
+    };
+
+    // a palette sized for the mask is kept
+    let (masked, replaced) = settings.with_mask(Some(mask.clone()));
+    assert_eq!(replaced, None);
+    assert_eq!(masked.source, settings.source);
+    assert_eq!(masked.mask, Some(mask.clone()));
+
+    // and replaced once the mask is cleared, the replaced source is returned
+    let (unmasked, replaced) = masked.with_mask(None);
+    assert_eq!(replaced, Some(settings.source.clone()));
+    assert_eq!(
+        unmasked.source,
+        ColorSource::SampledCube { count: SIZE * SIZE }
+    );
+    assert_eq!(unmasked.mask, None);
+    assert!(Engine::new(unmasked).is_ok());
+
+    let cube = Settings {
+        source: ColorSource::Cube { bits: 2 },
+        ..Settings::new(8, 8, 0)
+    };
+    let (masked, replaced) = cube.with_mask(Some(Mask::new(8, 8)));
+    assert_eq!(replaced, None);
+    assert_eq!(masked.source, ColorSource::Cube { bits: 2 });
+}
+
+#[test]
+fn rejects_mismatched_masks_and_forbidden_seeds() {
+    let settings = Settings {
+        mask: Some(Mask::new(4, 4)),
+        ..Settings::new(SIZE, SIZE, 0)
+    };
+    assert_eq!(
+        Engine::new(settings).err().unwrap(),
+        "the mask is 4x4 but the grid is 12x12"
+    );
+
+    let mask = walled_mask();
+    let settings = Settings {
+        source: ColorSource::SampledCube {
+            count: mask.placeable_count(),
+        },
+        seed_points: vec![SeedPoint::new(0, 5), SeedPoint::new(SIZE / 2, 3)],
+        mask: Some(mask),
+        ..Settings::new(SIZE, SIZE, 0)
+    };
+    assert_eq!(
+        Engine::new(settings).err().unwrap(),
+        "seed point 1 at 6,3 lies on a forbidden cell"
+    );
+}
+
+#[test]
+fn builds_masks_from_alpha_and_polygons() {
+    let rgba = [
+        9, 9, 9, 0, //
+        9, 9, 9, 255, //
+        9, 9, 9, 128, //
+        9, 9, 9, 1,
+    ];
+    let mask = Mask::from_alpha(2, 2, &rgba).unwrap();
+    assert_eq!(mask.get(0, 0), CellMask::Forbidden);
+    assert_eq!(mask.get(1, 0), CellMask::Allowed);
+    assert_eq!(mask.get(0, 1), CellMask::FillLast);
+    assert_eq!(mask.get(1, 1), CellMask::FillLast);
+    assert!(Mask::from_alpha(2, 2, &rgba[..12]).is_err());
+
+    // a square with a square hole
+    let outer = vec![(1.0, 1.0), (7.0, 1.0), (7.0, 7.0), (1.0, 7.0)];
+    let hole = vec![(3.0, 3.0), (5.0, 3.0), (5.0, 5.0), (3.0, 5.0)];
+    let mask = Mask::from_polygons(8, 8, &[outer, hole]);
+    assert_eq!(mask.count(CellMask::Allowed), 36 - 4);
+    assert_eq!(mask.get(1, 1), CellMask::Allowed);
+    assert_eq!(mask.get(6, 6), CellMask::Allowed);
+    assert_eq!(mask.get(0, 3), CellMask::Forbidden);
+    assert_eq!(mask.get(3, 3), CellMask::Forbidden);
+    assert_eq!(mask.get(7, 7), CellMask::Forbidden);
+}
+
+#[test]
+fn frontier_index_respects_the_mask() {
+    let mask = walled_mask();
+    let settings = Settings {
+        source: ColorSource::SampledCube {
+            count: mask.placeable_count(),
+        },
+        score: PlacementScore::AverageNeighbor,
+        mask: Some(mask),
+        ..Settings::new(SIZE, SIZE, 4)
+    };
+    let mut indexed = Engine::new(settings.clone()).unwrap();
+    assert!(indexed.uses_frontier_index());
+    let mut linear = Engine::new(settings).unwrap();
+    linear.set_frontier_index(false);
+
+    indexed.run();
+    linear.run();
+    assert_eq!(indexed.pixels(), linear.pixels());
+}
@@ -0,0 +1,136 @@
+use color_shredder::engine::{
+    ciede2000, srgb_to_lab, srgb_to_oklab, ColorSource, Engine, Metric, Settings,
+};
+
+fn assert_close(actual: [f64; 3], expected: [f64; 3], tolerance: f64) {
+    for channel in 0..3 {
+        assert!(
+            (actual[channel] - expected[channel]).abs() < tolerance,
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+}
+
+#[test]
+fn lab_matches_reference_values() {
+    assert_close(srgb_to_lab([255, 255, 255]), [100.0, 0.0, 0.0], 1e-3);
+    assert_close(srgb_to_lab([0, 0, 0]), [0.0, 0.0, 0.0], 1e-9);
+    assert_close(srgb_to_lab([255, 0, 0]), [53.2408, 80.0925, 67.2032], 1e-3);
+    assert_close(srgb_to_lab([0, 255, 0]), [87.7347, -86.1827, 83.1793], 1e-3);
+    assert_close(
+        srgb_to_lab([0, 0, 255]),
+        [32.2970, 79.1875, -107.8602],
+        1e-3,
+    );
+}
+
+#[test]
+fn oklab_matches_reference_values() {
+    assert_close(srgb_to_oklab([255, 255, 255]), [1.0, 0.0, 0.0], 1e-4);
+    assert_close(
+        srgb_to_oklab([255, 0, 0]),
+        [0.627955, 0.224863, 0.125846],
+        1e-5,
+    );
+    assert_close(
+        srgb_to_oklab([0, 255, 0]),
+        [0.866440, -0.233888, 0.179498],
+        1e-5,
+    );
+    assert_close(
+        srgb_to_oklab([0, 0, 255]),
+        [0.452014, -0.032457, -0.311528],
+        1e-5,
+    );
+}
+
+#[test]
+fn ciede2000_matches_sharma_test_data() {
+    // pairs from sharma, wu and dalal, "the ciede2000 color-difference formula"
+    let pairs = [
+        ([50.0, 2.6772, -79.7751], [50.0, 0.0, -82.7485], 2.0425),
+        ([50.0, 3.1571, -77.2803], [50.0, 0.0, -82.7485], 2.8615),
+        ([50.0, 0.0, 0.0], [50.0, -1.0, 2.0], 2.3669),
+        ([50.0, 2.49, -0.001], [50.0, -2.49, 0.0011], 7.2195),
+        ([50.0, 2.5, 0.0], [73.0, 25.0, -18.0], 27.1492),
+        ([50.0, 2.5, 0.0], [50.0, 3.2592, 0.335], 1.0000),
+        (
+            [60.2574, -34.0099, 36.2677],
+            [60.4626, -34.1751, 39.4387],
+            1.2644,
+        ),
+        (
+            [22.7233, 20.0904, -46.694],
+            [23.0331, 14.973, -42.5619],
+            2.0373,
+        ),
+        (
+            [90.9257, -0.5406, -0.9208],
+            [88.6381, -0.8985, -0.7239],
+            1.5381,
+        ),
+        ([2.0776, 0.0795, -1.135], [0.9033, -0.0636, -0.5514], 0.9082),
+    ];
+    for (lab1, lab2, expected) in pairs.iter() {
+        let delta_e = ciede2000(lab1, lab2);
+        assert!(
+            (delta_e - expected).abs() < 1e-4,
+            "{:?} {:?}: {} != {}",
+            lab1,
+            lab2,
+            delta_e,
+            expected
+        );
+        assert!((ciede2000(lab2, lab1) - delta_e).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn metrics_are_zero_on_equal_colors_and_positive_otherwise() {
+    for name in &[
+        "rgb",
+        "luma-weighted",
+        "redmean",
+        "cie76",
+        "ciede2000",
+        "oklab",
+    ] {
+        let metric: Metric = name.parse().unwrap();
+        assert_eq!(metric.to_string(), *name);
+
+        let metric = metric.implementation();
+        assert_eq!(metric.color_distance([12, 200, 99], [12, 200, 99]), 0.0);
+        assert!(metric.color_distance([12, 200, 99], [13, 200, 99]) > 0.0);
+    }
+    assert!("hsv".parse::<Metric>().unwrap_err().contains("'hsv'"));
+}
+
+#[test]
+fn metrics_change_the_image_but_not_the_colors() {
+    let run = |metric| {
+        let mut engine = Engine::new(Settings {
+            source: ColorSource::Cube { bits: 2 },
+            metric,
+            ..Settings::new(8, 8, 11)
+        })
+        .unwrap();
+        engine.run();
+        engine.pixels().to_vec()
+    };
+    let sorted = |mut pixels: Vec<u8>| {
+        let mut colors: Vec<_> = pixels
+            .chunks_exact_mut(4)
+            .map(|pixel| pixel.to_vec())
+            .collect();
+        colors.sort();
+        colors
+    };
+
+    let rgb = run(Metric::Rgb);
+    let oklab = run(Metric::Oklab);
+    assert_ne!(rgb, oklab);
+    assert_eq!(sorted(rgb), sorted(oklab));
+    assert_eq!(run(Metric::Ciede2000), run(Metric::Ciede2000));
+}
@@ -0,0 +1,163 @@
+use color_shredder::engine::{ColorSource, Engine, Neighborhood, PlacementScore, Settings};
+use std::collections::BTreeSet;
+
+const SIZE: usize = 9;
+
+fn engine(neighborhood: Neighborhood, seed: u64) -> Engine {
+    let settings = Settings {
+        source: ColorSource::SampledCube { count: SIZE * SIZE },
+        neighborhood,
+        ..Settings::new(SIZE, SIZE, seed)
+    };
+    Engine::new(settings).unwrap()
+}
+
+// the kernels spelled out independently of the engine
+fn expected_offsets(neighborhood: &Neighborhood, y: usize) -> Vec<(isize, isize)> {
+    match neighborhood {
+        Neighborhood::VonNeumann => vec![(0, -1), (-1, 0), (1, 0), (0, 1)],
+        Neighborhood::Moore => (-1..=1)
+            .flat_map(|dy| (-1..=1).map(move |dx| (dx, dy)))
+            .filter(|&offset| offset != (0, 0))
+            .collect(),
+        Neighborhood::Disk { radius: 2 } => (-2isize..=2)
+            .flat_map(|dy| (-2isize..=2).map(move |dx| (dx, dy)))
+            .filter(|&(dx, dy)| (dx, dy) != (0, 0) && dx * dx + dy * dy <= 4)
+            .collect(),
+        Neighborhood::Hexagonal if y.is_multiple_of(2) => {
+            vec![(-1, -1), (0, -1), (-1, 0), (1, 0), (-1, 1), (0, 1)]
+        }
+        Neighborhood::Hexagonal => vec![(0, -1), (1, -1), (-1, 0), (1, 0), (0, 1), (1, 1)],
+        Neighborhood::Custom(offsets) => offsets.iter().map(|&(dx, dy, _)| (dx, dy)).collect(),
+        _ => unreachable!(),
+    }
+}
+
+// empty cells with at least one filled cell in their kernel
+fn expected_frontier(engine: &Engine, neighborhood: &Neighborhood) -> BTreeSet<usize> {
+    (0..SIZE * SIZE)
+        .filter(|&cell| engine.cell_color(cell).is_none())
+        .filter(|&cell| {
+            let (x, y) = ((cell % SIZE) as isize, (cell / SIZE) as isize);
+            expected_offsets(neighborhood, y as usize)
+                .into_iter()
+                .any(|(dx, dy)| {
+                    let (nx, ny) = (x + dx, y + dy);
+                    (0..SIZE as isize).contains(&nx)
+                        && (0..SIZE as isize).contains(&ny)
+                        && engine.color_at(nx as usize, ny as usize).is_some()
+                })
+        })
+        .collect()
+}
+
+fn kernels() -> Vec<Neighborhood> {
+    vec![
+        Neighborhood::VonNeumann,
+        Neighborhood::Moore,
+        Neighborhood::Disk { radius: 2 },
+        Neighborhood::Hexagonal,
+        // asymmetric, cells only look to their right and two rows down
+        Neighborhood::Custom(vec![(1, 0, 1.0), (0, 2, 0.5)]),
+    ]
+}
+
+#[test]
+fn frontier_matches_the_kernel() {
+    for neighborhood in kernels() {
+        for seed in 0..3 {
+            let mut engine = engine(neighborhood.clone(), seed);
+            while engine.step().is_some() {
+                let frontier: BTreeSet<usize> = engine.frontier().iter().collect();
+                assert_eq!(
+                    frontier,
+                    expected_frontier(&engine, &neighborhood),
+                    "{} after {} placements",
+                    neighborhood,
+                    engine.placed()
+                );
+            }
+            assert_eq!(engine.placed(), SIZE * SIZE);
+        }
+    }
+}
+
+#[test]
+fn first_placement_opens_its_kernel() {
+    let expected_sizes = [
+        (Neighborhood::VonNeumann, 4),
+        (Neighborhood::Moore, 8),
+        (Neighborhood::Disk { radius: 2 }, 12),
+        (Neighborhood::Hexagonal, 6),
+    ];
+    for (neighborhood, size) in expected_sizes.iter() {
+        // find a seed whose first placement is far enough from the edges
+        let mut engine = (0..)
+            .map(|seed| {
+                let mut engine = engine(neighborhood.clone(), seed);
+                let cell = engine.step().unwrap().cell;
+                (engine, cell)
+            })
+            .find(|&(_, cell)| {
+                (2..SIZE - 2).contains(&(cell % SIZE)) && (2..SIZE - 2).contains(&(cell / SIZE))
+            })
+            .unwrap()
+            .0;
+        assert_eq!(engine.frontier().len(), *size, "{}", neighborhood);
+        engine.run();
+    }
+}
+
+#[test]
+fn names_round_trip() {
+    for neighborhood in kernels() {
+        let name = neighborhood.to_string();
+        assert_eq!(name.parse::<Neighborhood>().unwrap(), neighborhood);
+    }
+    assert_eq!(
+        "custom=1,0,1;-1,0,0.5".parse::<Neighborhood>().unwrap(),
+        Neighborhood::Custom(vec![(1, 0, 1.0), (-1, 0, 0.5)])
+    );
+}
+
+#[test]
+fn rejects_invalid_kernels() {
+    for name in &[
+        "ring",
+        "disk=0",
+        "disk=x",
+        "custom=",
+        "custom=0,0,1",
+        "custom=1,0,0",
+        "custom=1,0,1;1,0,2",
+        "custom=1,0",
+        "custom=20,0,1",
+    ] {
+        assert!(name.parse::<Neighborhood>().is_err(), "{}", name);
+    }
+
+    let settings = Settings {
+        neighborhood: Neighborhood::Custom(vec![]),
+        ..Settings::new(2, 2, 0)
+    };
+    assert!(Engine::new(settings).is_err());
+}
+
+#[test]
+fn frontier_index_matches_linear_scan_for_every_kernel() {
+    for neighborhood in kernels() {
+        let settings = Settings {
+            score: PlacementScore::AverageNeighbor,
+            neighborhood: neighborhood.clone(),
+            ..Settings::new(16, 16, 5)
+        };
+        let mut indexed = Engine::new(settings.clone()).unwrap();
+        assert!(indexed.uses_frontier_index());
+        let mut linear = Engine::new(settings).unwrap();
+        linear.set_frontier_index(false);
+
+        indexed.run();
+        linear.run();
+        assert_eq!(indexed.pixels(), linear.pixels(), "{}", neighborhood);
+    }
+}
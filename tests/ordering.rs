@@ -0,0 +1,99 @@
+use color_shredder::engine::{
+    all_colors, hilbert_index_bits, hue, luma, morton_index, saturation, ColorOrdering,
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+fn ordered(ordering: ColorOrdering, seed: u64) -> Vec<[u8; 3]> {
+    let mut colors = all_colors(3);
+    ordering.apply(&mut colors, &mut ChaCha8Rng::seed_from_u64(seed));
+    colors
+}
+
+#[test]
+fn sorted_orderings_are_monotonic() {
+    let is_sorted = |values: Vec<f64>| values.windows(2).all(|pair| pair[0] <= pair[1]);
+
+    let colors = ordered(ColorOrdering::Hue, 1);
+    assert!(is_sorted(colors.iter().map(|&color| hue(color)).collect()));
+    let colors = ordered(ColorOrdering::Luminance, 1);
+    assert!(is_sorted(
+        colors.iter().map(|&color| luma(color) as f64).collect()
+    ));
+    let colors = ordered(ColorOrdering::Saturation, 1);
+    assert!(is_sorted(
+        colors.iter().map(|&color| saturation(color)).collect()
+    ));
+    let colors = ordered(ColorOrdering::Morton, 1);
+    assert!(is_sorted(
+        colors
+            .iter()
+            .map(|&color| morton_index(color) as f64)
+            .collect()
+    ));
+}
+
+#[test]
+fn ties_are_broken_by_the_seed() {
+    // all grays share hue 0
+    assert_ne!(
+        ordered(ColorOrdering::Hue, 1),
+        ordered(ColorOrdering::Hue, 2)
+    );
+    assert_eq!(
+        ordered(ColorOrdering::Hue, 1),
+        ordered(ColorOrdering::Hue, 1)
+    );
+}
+
+#[test]
+fn hilbert_curve_steps_between_adjacent_lattice_points() {
+    let bits = 3;
+    let mut points: Vec<(u32, [u32; 3])> = Vec::new();
+    for r in 0..8 {
+        for g in 0..8 {
+            for b in 0..8 {
+                points.push((hilbert_index_bits([r, g, b], bits), [r, g, b]));
+            }
+        }
+    }
+    points.sort();
+
+    for (position, pair) in points.windows(2).enumerate() {
+        assert_eq!(pair[0].0, position as u32);
+        let step: u32 = (0..3)
+            .map(|c| (pair[0].1[c] as i32 - pair[1].1[c] as i32).unsigned_abs())
+            .sum();
+        assert_eq!(step, 1, "{:?} -> {:?}", pair[0].1, pair[1].1);
+    }
+}
+
+#[test]
+fn hue_bias_interpolates_between_shuffle_and_hue() {
+    let full = ordered(ColorOrdering::RandomHueBias { strength: 1.0 }, 4);
+    assert!(full.windows(2).all(|pair| hue(pair[0]) <= hue(pair[1])));
+
+    let none = ordered(ColorOrdering::RandomHueBias { strength: 0.0 }, 4);
+    assert!(none.windows(2).any(|pair| hue(pair[0]) > hue(pair[1])));
+}
+
+#[test]
+fn orderings_round_trip_through_their_names() {
+    for name in &[
+        "shuffle",
+        "hue",
+        "luminance",
+        "saturation",
+        "hilbert",
+        "morton",
+        "hue-bias=0.25",
+    ] {
+        let ordering: ColorOrdering = name.parse().unwrap();
+        assert_eq!(ordering.to_string(), *name);
+    }
+    assert!("hue-bias=2".parse::<ColorOrdering>().is_err());
+    assert!("rainbow"
+        .parse::<ColorOrdering>()
+        .unwrap_err()
+        .contains("'rainbow'"));
+}
@@ -0,0 +1,156 @@
+#![cfg(feature = "parallel")]
+
+use color_shredder::engine::{
+    CellMask, ColorSource, Engine, Mask, Metric, Parallelism, PlacementScore, SeedPoint, Settings,
+};
+
+fn run(settings: &Settings, parallelism: Parallelism, steps: usize) -> Engine {
+    let mut engine = Engine::new(settings.clone()).unwrap();
+    engine.set_parallelism(parallelism).unwrap();
+    engine.start_recording().unwrap();
+    engine.step_n(steps);
+    engine
+}
+
+// a lattice of seed points grows a frontier of thousands of cells within a
+// few hundred placements, enough to split scans across threads
+fn seed_lattice(width: usize, height: usize, spacing: usize) -> Vec<SeedPoint> {
+    (0..height)
+        .step_by(spacing)
+        .flat_map(|y| {
+            (0..width)
+                .step_by(spacing)
+                .map(move |x| SeedPoint::new(x, y))
+        })
+        .collect()
+}
+
+#[test]
+fn exact_mode_matches_the_serial_engine() {
+    let mut mask = Mask::new(40, 40);
+    for y in 0..40 {
+        mask.set(20, y, CellMask::Forbidden);
+        mask.set(0, y, CellMask::FillLast);
+    }
+    let variants = vec![
+        Settings {
+            score: PlacementScore::MinNeighbor,
+            seed_points: seed_lattice(96, 96, 5),
+            ..Settings::new(96, 96, 1)
+        },
+        Settings {
+            metric: Metric::Ciede2000,
+            score: PlacementScore::WeightedNeighbor,
+            seed_points: seed_lattice(80, 60, 4),
+            ..Settings::new(80, 60, 2)
+        },
+        Settings {
+            metric: Metric::Oklab,
+            seed_points: seed_lattice(96, 96, 5),
+            ..Settings::new(96, 96, 3)
+        },
+    ];
+    for settings in variants {
+        let steps = settings.seed_points.len() + 60;
+        let serial = run(&settings, Parallelism::Serial, steps);
+        let exact = run(&settings, Parallelism::Exact, steps);
+        assert!(serial.frontier().len() > 600);
+        assert_eq!(exact.recording(), serial.recording(), "{:?}", settings);
+        assert!(exact.pixels() == serial.pixels());
+    }
+
+    // whole runs, including a mask releasing its fill last cells
+    let settings = Settings {
+        source: ColorSource::SampledCube {
+            count: mask.placeable_count(),
+        },
+        score: PlacementScore::MinNeighbor,
+        mask: Some(mask),
+        ..Settings::new(40, 40, 4)
+    };
+    let serial = run(&settings, Parallelism::Serial, usize::MAX);
+    let exact = run(&settings, Parallelism::Exact, usize::MAX);
+    assert!(exact.is_finished());
+    assert!(exact.pixels() == serial.pixels());
+}
+
+#[test]
+fn switching_modes_mid_run_keeps_exact_runs_identical() {
+    let settings = Settings {
+        score: PlacementScore::MinNeighbor,
+        ..Settings::new(32, 32, 5)
+    };
+    let serial = run(&settings, Parallelism::Serial, usize::MAX);
+    let mut engine = run(&settings, Parallelism::Exact, 300);
+    engine.set_parallelism(Parallelism::Serial).unwrap();
+    engine.step_n(300);
+    engine.set_parallelism(Parallelism::Exact).unwrap();
+    engine.run();
+    assert!(engine.pixels() == serial.pixels());
+}
+
+#[test]
+fn batched_mode_places_every_color_once() {
+    let mut mask = Mask::new(48, 48);
+    mask.set(10, 10, CellMask::Forbidden);
+    mask.set(47, 47, CellMask::FillLast);
+    let variants = vec![
+        Settings {
+            score: PlacementScore::MinNeighbor,
+            ..Settings::new(48, 48, 6)
+        },
+        // the frontier index answers the queries of a batch in parallel
+        Settings::new(64, 64, 7),
+        Settings {
+            source: ColorSource::SampledCube {
+                count: mask.placeable_count(),
+            },
+            seed_points: vec![SeedPoint::new(5, 5), SeedPoint::new(40, 3)],
+            mask: Some(mask),
+            ..Settings::new(48, 48, 8)
+        },
+    ];
+    for settings in variants {
+        for &batch in &[7, 64] {
+            let engine = run(&settings, Parallelism::Batched(batch), usize::MAX);
+            assert!(engine.is_finished());
+            let report = engine.validate();
+            assert!(report.is_valid(), "{:?}: {}", settings, report);
+            assert_eq!(engine.recording().unwrap().len(), engine.placed());
+        }
+    }
+}
+
+#[test]
+fn batches_of_one_color_match_the_serial_engine() {
+    let settings = Settings {
+        score: PlacementScore::AverageNeighbor,
+        metric: Metric::Rgb,
+        ..Settings::new(40, 40, 9)
+    };
+    let serial = run(&settings, Parallelism::Serial, usize::MAX);
+    let batched = run(&settings, Parallelism::Batched(1), usize::MAX);
+    assert!(batched.pixels() == serial.pixels());
+}
+
+#[test]
+fn parses_parallelism_names() {
+    for name in &["serial", "exact", "batched=256"] {
+        let parallelism: Parallelism = name.parse().unwrap();
+        assert_eq!(parallelism.to_string(), *name);
+    }
+    assert_eq!(
+        "batched=0".parse::<Parallelism>().err().unwrap(),
+        "a parallel batch needs at least one color"
+    );
+    assert_eq!(
+        "batched=many".parse::<Parallelism>().err().unwrap(),
+        "invalid parallel batch size 'many'"
+    );
+    assert!("threads".parse::<Parallelism>().is_err());
+
+    let mut engine = Engine::new(Settings::new(4, 4, 0)).unwrap();
+    assert_eq!(engine.parallelism(), Parallelism::Serial);
+    assert!(engine.set_parallelism(Parallelism::Batched(0)).is_err());
+    assert_eq!(engine.parallelism(), Parallelism::Serial);
+}
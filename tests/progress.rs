@@ -0,0 +1,48 @@
+use color_shredder::engine::{Engine, ProgressMeter, Settings};
+use std::cell::Cell;
+
+#[test]
+fn step_limit_caps_an_unlimited_budget() {
+    let mut engine = Engine::new(Settings::new(16, 16, 1)).unwrap();
+    assert_eq!(engine.step_within(f64::INFINITY, 100, || 0.0), 100);
+    assert_eq!(engine.placed(), 100);
+    assert_eq!(engine.remaining(), 156);
+
+    // no limit runs until the colors are used up
+    assert_eq!(engine.step_within(f64::INFINITY, 0, || 0.0), 156);
+    assert!(engine.is_finished());
+    assert_eq!(engine.step_within(f64::INFINITY, 0, || 0.0), 0);
+}
+
+#[test]
+fn time_budget_stops_stepping() {
+    let mut engine = Engine::new(Settings::new(16, 16, 1)).unwrap();
+    // every clock read advances the fake clock by one millisecond
+    let clock = Cell::new(0.0);
+    let now = || {
+        clock.set(clock.get() + 1.0);
+        clock.get()
+    };
+
+    let placed = engine.step_within(3.0, 0, now);
+    assert!(placed > 0 && placed < 256, "placed {}", placed);
+    assert_eq!(engine.step_within(0.0, 0, || 0.0), 0);
+}
+
+#[test]
+fn meter_reports_the_rate_over_its_window() {
+    let mut meter = ProgressMeter::new(1000.0);
+    assert_eq!(meter.placements_per_second(), 0.0);
+
+    meter.record(0.0, 0);
+    meter.record(500.0, 1000);
+    assert_eq!(meter.placements_per_second(), 2000.0);
+
+    // older samples fall out of the window
+    meter.record(1500.0, 1100);
+    meter.record(2500.0, 1200);
+    assert_eq!(meter.placements_per_second(), 100.0);
+
+    meter.reset();
+    assert_eq!(meter.placements_per_second(), 0.0);
+}
@@ -0,0 +1,226 @@
+use color_shredder::engine::{
+    Engine, EngineWorker, GridMirror, Metric, PlacementBatch, Settings, WorkerRequest,
+    WorkerResponse, PROTOCOL_VERSION,
+};
+
+fn settings() -> Settings {
+    Settings {
+        metric: Metric::Oklab,
+        ..Settings::new(40, 30, 8)
+    }
+}
+
+#[test]
+fn messages_round_trip() {
+    let requests = vec![
+        WorkerRequest::Start(settings()),
+        WorkerRequest::Pause,
+        WorkerRequest::Resume,
+    ];
+    for request in requests {
+        assert_eq!(WorkerRequest::decode(&request.encode()).unwrap(), request);
+    }
+
+    let responses = vec![
+        WorkerResponse::Started {
+            width: 40,
+            height: 30,
+            remaining: 1200,
+        },
+        WorkerResponse::Placements(PlacementBatch {
+            placed: 2,
+            remaining: 1198,
+            deltas: vec![(17, [1, 2, 3, 255]), (1199, [255, 0, 9, 255])],
+        }),
+        WorkerResponse::Placements(PlacementBatch::default()),
+        WorkerResponse::Error(String::from("grid must not be empty, got 0x3")),
+    ];
+    for response in responses {
+        assert_eq!(
+            WorkerResponse::decode(&response.encode()).unwrap(),
+            response
+        );
+    }
+}
+
+#[test]
+fn message_layout_is_fixed() {
+    assert_eq!(WorkerRequest::Pause.encode(), vec![PROTOCOL_VERSION, 1]);
+    assert_eq!(WorkerRequest::Resume.encode(), vec![PROTOCOL_VERSION, 2]);
+
+    let batch = WorkerResponse::Placements(PlacementBatch {
+        placed: 5,
+        remaining: 3,
+        deltas: vec![(258, [10, 20, 30, 255])],
+    });
+    let mut expected = vec![PROTOCOL_VERSION, 1];
+    expected.extend(&5u64.to_le_bytes());
+    expected.extend(&3u64.to_le_bytes());
+    expected.extend(&1u64.to_le_bytes());
+    expected.extend(&[2, 1, 0, 0, 10, 20, 30, 255]);
+    assert_eq!(batch.encode(), expected);
+}
+
+#[test]
+fn rejects_malformed_messages() {
+    assert_eq!(
+        WorkerRequest::decode(&[PROTOCOL_VERSION + 1, 1])
+            .err()
+            .unwrap(),
+        format!(
+            "worker protocol version {} is not supported, expected {}",
+            PROTOCOL_VERSION + 1,
+            PROTOCOL_VERSION
+        )
+    );
+    assert_eq!(
+        WorkerRequest::decode(&[PROTOCOL_VERSION, 7]).err().unwrap(),
+        "unknown worker request tag 7"
+    );
+    assert_eq!(
+        WorkerResponse::decode(&[PROTOCOL_VERSION, 9])
+            .err()
+            .unwrap(),
+        "unknown worker response tag 9"
+    );
+    assert_eq!(
+        WorkerRequest::decode(&[PROTOCOL_VERSION, 1, 0])
+            .err()
+            .unwrap(),
+        "worker message has trailing data"
+    );
+    assert!(WorkerRequest::decode(&[]).is_err());
+
+    let start = WorkerRequest::Start(settings()).encode();
+    for length in 0..start.len() {
+        assert!(WorkerRequest::decode(&start[..length]).is_err());
+    }
+    let batch = WorkerResponse::Placements(PlacementBatch {
+        placed: 1,
+        remaining: 0,
+        deltas: vec![(0, [0; 4])],
+    })
+    .encode();
+    for length in 0..batch.len() {
+        assert!(WorkerResponse::decode(&batch[..length]).is_err());
+    }
+}
+
+// feeds every message of `worker` into `mirror` like the main thread does
+fn drain(worker: &mut EngineWorker, mirror: &mut GridMirror, step_limit: usize) -> usize {
+    let mut batches = 0;
+    while let Some(message) = worker.step(f64::INFINITY, step_limit, || 0.0) {
+        match WorkerResponse::decode(&message).unwrap() {
+            WorkerResponse::Placements(batch) => {
+                assert!(batch.deltas.len() <= step_limit);
+                mirror.apply(&batch).unwrap();
+            }
+            response => panic!("unexpected {:?}", response),
+        }
+        batches += 1;
+    }
+    batches
+}
+
+#[test]
+fn mirrors_the_grid_of_the_worker() {
+    let mut worker = EngineWorker::new();
+    assert!(!worker.is_running());
+    let started = worker
+        .handle(&WorkerRequest::Start(settings()).encode())
+        .unwrap();
+    let mut mirror = match WorkerResponse::decode(&started).unwrap() {
+        WorkerResponse::Started {
+            width,
+            height,
+            remaining,
+        } => GridMirror::new(width, height, remaining).unwrap(),
+        response => panic!("unexpected {:?}", response),
+    };
+    assert_eq!(mirror.remaining(), 1200);
+
+    assert!(worker.is_running());
+    assert_eq!(drain(&mut worker, &mut mirror, 100), 12);
+    assert!(!worker.is_running());
+
+    let mut engine = Engine::new(settings()).unwrap();
+    engine.run();
+    assert_eq!(mirror.pixels(), engine.pixels());
+    assert_eq!((mirror.placed(), mirror.remaining()), (1200, 0));
+    assert!(!mirror.take_dirty_rects().is_empty());
+}
+
+#[test]
+fn pauses_resumes_and_restarts() {
+    let mut worker = EngineWorker::new();
+    worker.handle(&WorkerRequest::Start(settings()).encode());
+    let mut mirror = GridMirror::new(40, 30, 1200).unwrap();
+    worker.step(f64::INFINITY, 50, || 0.0).unwrap();
+
+    assert_eq!(worker.handle(&WorkerRequest::Pause.encode()), None);
+    assert!(!worker.is_running());
+    assert_eq!(worker.step(f64::INFINITY, 50, || 0.0), None);
+    assert_eq!(worker.handle(&WorkerRequest::Resume.encode()), None);
+    assert!(worker.is_running());
+
+    // a restart sends the new run from its first placement
+    let restarted = Settings {
+        seed: 9,
+        ..settings()
+    };
+    worker.handle(&WorkerRequest::Start(restarted.clone()).encode());
+    drain(&mut worker, &mut mirror, 500);
+    let mut engine = Engine::new(restarted).unwrap();
+    engine.run();
+    assert_eq!(mirror.pixels(), engine.pixels());
+}
+
+#[test]
+fn reports_errors_as_messages() {
+    let mut worker = EngineWorker::new();
+    let response = worker
+        .handle(&WorkerRequest::Start(Settings::new(0, 3, 1)).encode())
+        .unwrap();
+    assert_eq!(
+        WorkerResponse::decode(&response).unwrap(),
+        WorkerResponse::Error(String::from("grid must not be empty, got 0x3"))
+    );
+    let response = worker.handle(&[0xff]).unwrap();
+    assert!(matches!(
+        WorkerResponse::decode(&response).unwrap(),
+        WorkerResponse::Error(_)
+    ));
+    assert!(!worker.is_running());
+
+    let mut mirror = GridMirror::new(4, 4, 16).unwrap();
+    let batch = PlacementBatch {
+        placed: 2,
+        remaining: 14,
+        deltas: vec![(3, [1, 1, 1, 255]), (16, [2, 2, 2, 255])],
+    };
+    assert_eq!(
+        mirror.apply(&batch).err().unwrap(),
+        "worker sent cell 16 for the 4x4 grid"
+    );
+    assert!(mirror.pixels().iter().all(|&byte| byte == 0));
+}
+
+#[test]
+fn mirrors_only_grids_the_engine_could_run() {
+    assert_eq!(
+        GridMirror::new(0, 5, 0).err().unwrap(),
+        "the worker started an invalid 0x5 grid"
+    );
+    assert_eq!(
+        GridMirror::new(usize::MAX, 2, 0).err().unwrap(),
+        format!("the worker started an invalid {}x2 grid", usize::MAX)
+    );
+    assert_eq!(
+        GridMirror::new(1 << 16, 1 << 16, 0).err().unwrap(),
+        "the worker started an invalid 65536x65536 grid"
+    );
+    assert_eq!(
+        GridMirror::new(4, 4, 17).err().unwrap(),
+        "the worker started with 17 colors for 16 cells"
+    );
+}
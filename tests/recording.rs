@@ -0,0 +1,207 @@
+use color_shredder::engine::{
+    encode_apng, AnimationSettings, CellMask, ColorSource, Engine, Mask, Neighborhood, Recording,
+    Settings,
+};
+
+fn settings() -> Settings {
+    let mut mask = Mask::new(24, 20);
+    for y in 4..16 {
+        mask.set(12, y, CellMask::Forbidden);
+    }
+    mask.set(0, 0, CellMask::FillLast);
+    Settings {
+        source: ColorSource::SampledCube {
+            count: mask.placeable_count(),
+        },
+        neighborhood: Neighborhood::Hexagonal,
+        mask: Some(mask),
+        ..Settings::new(24, 20, 17)
+    }
+}
+
+fn recorded(settings: Settings) -> Engine {
+    let mut engine = Engine::new(settings).unwrap();
+    engine.start_recording().unwrap();
+    engine.run();
+    engine
+}
+
+#[test]
+fn records_every_placement_in_order() {
+    let mut engine = Engine::new(settings()).unwrap();
+    engine.start_recording().unwrap();
+    let placements: Vec<u32> = std::iter::from_fn(|| engine.step())
+        .map(|placement| placement.cell as u32)
+        .collect();
+    let recording = engine.recording().unwrap();
+    assert_eq!(recording.cells(), &placements[..]);
+    assert_eq!(recording.len(), settings().placeable_cells());
+    assert_eq!(recorded(settings()).recording(), Some(recording));
+
+    let mut started = Engine::new(settings()).unwrap();
+    assert!(started.recording().is_none());
+    started.step();
+    assert_eq!(
+        started.start_recording().err().unwrap(),
+        "cannot start recording after 1 placements"
+    );
+}
+
+#[test]
+fn replays_any_moment_from_the_finished_grid() {
+    let finished = recorded(settings());
+    let recording = finished.recording().unwrap();
+    let mut engine = Engine::new(settings()).unwrap();
+    for &stop in &[0, 1, 57, 200, recording.len()] {
+        engine.step_n(stop - engine.placed());
+        assert_eq!(recording.frame(finished.pixels(), stop), engine.pixels());
+    }
+}
+
+#[test]
+fn recordings_round_trip_compactly() {
+    let engine = recorded(settings());
+    let recording = engine.recording().unwrap();
+    let bytes = recording.to_bytes();
+    assert_eq!(&Recording::from_bytes(&bytes).unwrap(), recording);
+    // mostly steps to nearby cells
+    assert!(bytes.len() < recording.len() * 2, "{} bytes", bytes.len());
+
+    assert_eq!(
+        Recording::from_bytes(b"CSCP").err().unwrap(),
+        "not a color shredder recording"
+    );
+    for length in 0..bytes.len() {
+        assert!(Recording::from_bytes(&bytes[..length]).is_err());
+    }
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert_eq!(
+        Recording::from_bytes(&trailing).err().unwrap(),
+        "recording has trailing data"
+    );
+
+    let mut twice = Recording::new(4, 4);
+    twice.push(3);
+    twice.push(3);
+    assert_eq!(
+        Recording::from_bytes(&twice.to_bytes()).err().unwrap(),
+        "recording places cell 3 twice, again at step 1"
+    );
+    let mut outside = Recording::new(4, 4);
+    outside.push(16);
+    assert!(Recording::from_bytes(&outside.to_bytes()).is_err());
+}
+
+#[test]
+fn huge_headers_do_not_allocate_their_grid() {
+    // a few bytes claiming a 65536x65535 grid without placements
+    let bytes = Recording::new(65536, 65535).to_bytes();
+    assert!(bytes.len() < 40);
+    assert_eq!(Recording::size_of(&bytes).unwrap(), (65536, 65535));
+    let recording = Recording::from_bytes(&bytes).unwrap();
+    assert!(recording.is_empty());
+    assert_eq!((recording.width(), recording.height()), (65536, 65535));
+
+    assert_eq!(
+        Recording::size_of(&Recording::new(1 << 16, 1 << 16).to_bytes())
+            .err()
+            .unwrap(),
+        "a 65536x65536 recording is too large"
+    );
+}
+
+#[test]
+fn checkpoints_carry_the_recording() {
+    let finished = recorded(settings());
+    let mut engine = Engine::new(settings()).unwrap();
+    engine.start_recording().unwrap();
+    engine.step_n(150);
+
+    let mut resumed = Engine::from_checkpoint(&engine.checkpoint()).unwrap();
+    assert_eq!(resumed.recording(), engine.recording());
+    resumed.run();
+    assert_eq!(resumed.recording(), finished.recording());
+
+    // version 1 checkpoints end before the recording flag
+    let mut unrecorded = Engine::new(settings()).unwrap();
+    unrecorded.step_n(150);
+    let mut version_1 = unrecorded.checkpoint();
+    assert_eq!(version_1.pop(), Some(0));
+    version_1[4..6].copy_from_slice(&1u16.to_le_bytes());
+    let resumed = Engine::from_checkpoint(&version_1).unwrap();
+    assert!(resumed.recording().is_none());
+    assert_eq!(resumed.pixels(), unrecorded.pixels());
+}
+
+// every frame of an animated png composited onto the canvas, frame delays in
+// milliseconds
+fn decode_frames(apng: &[u8]) -> Vec<(Vec<u8>, u16)> {
+    let mut reader = png::Decoder::new(apng).read_info().unwrap();
+    let (width, height) = reader.info().size();
+    let frame_count = reader.info().animation_control().unwrap().num_frames;
+    let mut canvas = vec![0; (width * height * 4) as usize];
+    let mut frames = Vec::new();
+    for _ in 0..frame_count {
+        let mut data = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut data).unwrap();
+        let control = *reader.info().frame_control().unwrap();
+        assert_eq!(control.blend_op, png::BlendOp::Source);
+        assert_eq!(control.dispose_op, png::DisposeOp::None);
+        for y in 0..info.height {
+            for x in 0..info.width {
+                let source = ((y * info.width + x) * 4) as usize;
+                let target = (((control.y_offset + y) * width + control.x_offset + x) * 4) as usize;
+                canvas[target..target + 4].copy_from_slice(&data[source..source + 4]);
+            }
+        }
+        let delay = control.delay_num as u32 * 1000 / control.delay_den as u32;
+        frames.push((canvas.clone(), delay as u16));
+    }
+    frames
+}
+
+#[test]
+fn exports_the_recording_as_an_animated_png() {
+    let engine = recorded(settings());
+    let recording = engine.recording().unwrap();
+    let animation = AnimationSettings {
+        placements_per_frame: 40,
+        frame_delay_ms: 30,
+        final_delay_ms: 1500,
+    };
+    let frames = decode_frames(&engine.encode_animation(&animation).unwrap());
+    assert_eq!(frames.len(), recording.len().div_ceil(40));
+    for (index, (frame, delay)) in frames.iter().enumerate() {
+        let placements = ((index + 1) * 40).min(recording.len());
+        assert_eq!(frame, &recording.frame(engine.pixels(), placements));
+        let last = index + 1 == frames.len();
+        assert_eq!(*delay, if last { 1500 } else { 30 });
+    }
+
+    let spread = AnimationSettings::with_frame_count(recording.len(), 7, 50);
+    assert_eq!(spread.frame_count(recording.len()), 7);
+    let frames = decode_frames(&engine.encode_animation(&spread).unwrap());
+    assert_eq!(frames.len(), 7);
+    assert_eq!(frames[6].0, engine.pixels());
+}
+
+#[test]
+fn animates_empty_and_unrecorded_runs() {
+    let mut engine = Engine::new(settings()).unwrap();
+    assert_eq!(
+        engine
+            .encode_animation(&AnimationSettings::with_frame_count(0, 10, 40))
+            .err()
+            .unwrap(),
+        "the run was not recorded, start recording before the first step"
+    );
+
+    engine.start_recording().unwrap();
+    let animation = AnimationSettings::with_frame_count(0, 10, 40);
+    let frames = decode_frames(&engine.encode_animation(&animation).unwrap());
+    assert_eq!(frames.len(), 1);
+    assert!(frames[0].0.iter().all(|&byte| byte == 0));
+
+    assert!(encode_apng(engine.recording().unwrap(), &[0; 16], &animation).is_err());
+}
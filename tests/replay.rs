@@ -0,0 +1,136 @@
+use color_shredder::engine::{decode_png, Engine, Recording, Replay, Settings};
+
+fn recorded(placements: usize) -> Engine {
+    let mut engine = Engine::new(Settings::new(150, 70, 23)).unwrap();
+    engine.start_recording().unwrap();
+    engine.step_n(placements);
+    engine
+}
+
+fn covered(replay: &mut Replay, width: usize) -> Vec<bool> {
+    let mut covered = vec![false; replay.pixels().len() / 4];
+    for rect in replay.take_dirty_rects() {
+        for y in rect.y..rect.y + rect.height {
+            for x in rect.x..rect.x + rect.width {
+                covered[y * width + x] = true;
+            }
+        }
+    }
+    covered
+}
+
+#[test]
+fn seeks_in_both_directions() {
+    let engine = recorded(150 * 70);
+    let recording = engine.recording().unwrap().clone();
+    let mut replay = engine.replay().unwrap();
+    assert_eq!(replay.position(), 0);
+    assert!(replay.pixels().iter().all(|&byte| byte == 0));
+
+    for &position in &[4000, 4100, 300, 0, 150 * 70, 7000, 20_000] {
+        let before = replay.pixels().to_vec();
+        replay.seek(position);
+        let position = position.min(replay.len());
+        assert_eq!(replay.position(), position);
+        assert_eq!(
+            replay.pixels(),
+            &recording.frame(engine.pixels(), position)[..]
+        );
+
+        // every changed texel is in a dirty rect
+        let covered = covered(&mut replay, 150);
+        for (cell, pixel) in replay.pixels().chunks_exact(4).enumerate() {
+            if pixel != &before[cell * 4..cell * 4 + 4] {
+                assert!(
+                    covered[cell],
+                    "cell {} changed outside the dirty rects",
+                    cell
+                );
+            }
+        }
+    }
+
+    // a short scrub only touches the tiles it changes
+    replay.seek(5000);
+    replay.take_dirty_rects();
+    replay.seek(5010);
+    assert!(
+        covered(&mut replay, 150)
+            .iter()
+            .filter(|&&cell| cell)
+            .count()
+            < 150 * 70 / 2
+    );
+}
+
+#[test]
+fn plays_at_variable_speed() {
+    let mut replay = recorded(1000).replay().unwrap();
+    assert!(!replay.tick(0.0));
+
+    replay.set_speed(500.0);
+    assert!(replay.tick(100.0));
+    assert_eq!(replay.position(), 50);
+
+    // fractions carry over between ticks
+    replay.set_speed(10.0);
+    for tick in 1..=10 {
+        replay.tick(100.0 + tick as f64 * 50.0);
+    }
+    assert_eq!(replay.position(), 55);
+
+    replay.set_speed(-200.0);
+    replay.tick(800.0);
+    assert_eq!(replay.position(), 15);
+    replay.tick(5000.0);
+    assert_eq!(replay.position(), 0);
+
+    replay.set_speed(1e6);
+    replay.tick(6000.0);
+    assert_eq!(replay.position(), 1000);
+
+    replay.set_speed(0.0);
+    assert!(!replay.tick(9000.0));
+    assert_eq!(replay.speed(), 0.0);
+}
+
+#[test]
+fn loads_runs_from_exported_pngs() {
+    let engine = recorded(2500);
+    let (width, height, grid) = decode_png(&engine.encode_png(true).unwrap()).unwrap();
+    assert_eq!((width, height), (150, 70));
+    let recording = Recording::from_bytes(&engine.recording().unwrap().to_bytes()).unwrap();
+    let mut replay = Replay::new(recording, grid).unwrap();
+    replay.seek(2500);
+    assert_eq!(replay.pixels(), engine.pixels());
+}
+
+#[test]
+fn rejects_grids_that_do_not_match_the_recording() {
+    let engine = recorded(100);
+    let recording = engine.recording().unwrap().clone();
+
+    assert_eq!(
+        Replay::new(recording.clone(), vec![0; 16]).err().unwrap(),
+        "grid of 16 bytes does not match the 150x70 recording"
+    );
+
+    let mut grid = engine.pixels().to_vec();
+    let cell = recording.cells()[7] as usize;
+    grid[cell * 4 + 3] = 0;
+    assert_eq!(
+        Replay::new(recording.clone(), grid).err().unwrap(),
+        format!(
+            "the recording places cell {} which is empty in the grid",
+            cell
+        )
+    );
+
+    let mut grid = engine.pixels().to_vec();
+    let empty = (0..150 * 70).find(|&cell| grid[cell * 4 + 3] == 0).unwrap();
+    grid[empty * 4 + 3] = 255;
+    assert_eq!(
+        Replay::new(recording, grid).err().unwrap(),
+        "the grid has 101 filled cells but the recording 100 placements"
+    );
+}
@@ -0,0 +1,62 @@
+use color_shredder::engine::{
+    ColorOrdering, ColorSource, Engine, Metric, PlacementScore, Settings,
+};
+
+// grays sorted by luminance on a 3x2 grid:
+//
+//   0 1 2
+//   3 4 5
+//
+// with seed 16 the first two placements are 0 at cell 1 and 40 at cell 2
+fn placement_sequence(score: PlacementScore) -> Vec<usize> {
+    let grays = [0, 40, 100, 110, 200, 250];
+    let mut engine = Engine::new(Settings {
+        source: ColorSource::Palette(grays.iter().map(|&v| [v, v, v]).collect()),
+        ordering: ColorOrdering::Luminance,
+        metric: Metric::Rgb,
+        score,
+        ..Settings::new(3, 2, 16)
+    })
+    .unwrap();
+    std::iter::from_fn(|| engine.step())
+        .map(|placement| placement.cell)
+        .collect()
+}
+
+#[test]
+fn min_neighbor_sequence() {
+    // 100 ties between cells 4 and 5, which both touch 40; 110 then touches
+    // 100 from every open cell and the tie goes to cell 5
+    assert_eq!(
+        placement_sequence(PlacementScore::MinNeighbor),
+        vec![1, 2, 4, 5, 3, 0]
+    );
+}
+
+#[test]
+fn average_neighbor_sequence() {
+    // 110 prefers cells 0 and 3 (mean 50) over cell 5 (mean 46.7)
+    assert_eq!(
+        placement_sequence(PlacementScore::AverageNeighbor),
+        vec![1, 2, 4, 3, 0, 5]
+    );
+}
+
+#[test]
+fn weighted_neighbor_sequence() {
+    // 100 goes to cell 5 where 40 is an orthogonal neighbor and 0 a diagonal
+    // one, cell 4 has them the other way around
+    assert_eq!(
+        placement_sequence(PlacementScore::WeightedNeighbor),
+        vec![1, 2, 5, 4, 3, 0]
+    );
+}
+
+#[test]
+fn scores_round_trip_through_their_names() {
+    for name in &["min", "average", "weighted"] {
+        let score: PlacementScore = name.parse().unwrap();
+        assert_eq!(score.to_string(), *name);
+    }
+    assert!("max".parse::<PlacementScore>().is_err());
+}
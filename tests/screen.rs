@@ -0,0 +1,103 @@
+use color_shredder::screen::{
+    get_transform_from_canvas_dimensions, grid_line, screen_to_grid, DisplayArea,
+};
+
+// the display square `app_state::update_dynamic_data` computes for a canvas
+fn display_area(canvas_width: f32, canvas_height: f32) -> DisplayArea {
+    let half_display_size = 0.9 * canvas_width.min(canvas_height) / 2.0;
+    DisplayArea {
+        bottom: canvas_height / 2.0 - half_display_size,
+        top: canvas_height / 2.0 + half_display_size,
+        left: canvas_width / 2.0 - half_display_size,
+        right: canvas_width / 2.0 + half_display_size,
+        canvas_height,
+        canvas_width,
+    }
+}
+
+// where the transform draws a point of the grid quad, in canvas pixels from
+// the top left corner
+fn quad_to_screen(area: &DisplayArea, quad_x: f32, quad_y: f32) -> (f32, f32) {
+    let transform = get_transform_from_canvas_dimensions(area);
+    let clip_x = transform[0] * quad_x + transform[12];
+    let clip_y = transform[5] * quad_y + transform[13];
+    (
+        (clip_x + 1.0) / 2.0 * area.canvas_width,
+        (1.0 - clip_y) / 2.0 * area.canvas_height,
+    )
+}
+
+#[test]
+fn inverts_the_display_transform() {
+    let (width, height) = (64, 48);
+    for &(canvas_width, canvas_height) in &[(800.0, 600.0), (600.0, 800.0), (1920.0, 1080.0)] {
+        let area = display_area(canvas_width, canvas_height);
+        for row in 0..height {
+            for column in 0..width {
+                // cell centers, row 0 is drawn at the top of the quad
+                let quad_x = (column as f32 + 0.5) / width as f32;
+                let quad_y = 1.0 - (row as f32 + 0.5) / height as f32;
+                let (x, y) = quad_to_screen(&area, quad_x, quad_y);
+                assert_eq!(
+                    screen_to_grid(&area, x, y, width, height),
+                    Some((column, row)),
+                    "{}x{} canvas at {},{}",
+                    canvas_width,
+                    canvas_height,
+                    x,
+                    y
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn maps_the_display_corners() {
+    // an 800x600 canvas shows the grid in the square from 130,30 to 670,570
+    let area = display_area(800.0, 600.0);
+    assert_eq!(screen_to_grid(&area, 130.5, 30.5, 512, 512), Some((0, 0)));
+    assert_eq!(
+        screen_to_grid(&area, 669.5, 569.5, 512, 512),
+        Some((511, 511))
+    );
+    assert_eq!(screen_to_grid(&area, 669.5, 30.5, 512, 512), Some((511, 0)));
+    assert_eq!(
+        screen_to_grid(&area, 400.0, 300.0, 512, 512),
+        Some((256, 256))
+    );
+}
+
+#[test]
+fn ignores_points_outside_the_display_area() {
+    let area = display_area(800.0, 600.0);
+    for &(x, y) in &[
+        (129.0, 300.0),
+        (671.0, 300.0),
+        (400.0, 29.0),
+        (400.0, 571.0),
+        (0.0, 0.0),
+    ] {
+        assert_eq!(screen_to_grid(&area, x, y, 512, 512), None, "{},{}", x, y);
+    }
+}
+
+#[test]
+fn draws_connected_grid_lines() {
+    assert_eq!(grid_line((2, 3), (2, 3)), vec![(2, 3)]);
+    assert_eq!(
+        grid_line((0, 0), (3, 0)),
+        vec![(0, 0), (1, 0), (2, 0), (3, 0)]
+    );
+    assert_eq!(grid_line((2, 2), (0, 0)), vec![(2, 2), (1, 1), (0, 0)]);
+
+    let line = grid_line((1, 7), (9, 2));
+    assert_eq!(line.first(), Some(&(1, 7)));
+    assert_eq!(line.last(), Some(&(9, 2)));
+    assert_eq!(line.len(), 9);
+    for pair in line.windows(2) {
+        let (a, b) = (pair[0], pair[1]);
+        assert!((a.0 as isize - b.0 as isize).abs() <= 1);
+        assert!((a.1 as isize - b.1 as isize).abs() <= 1);
+    }
+}
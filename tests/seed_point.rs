@@ -0,0 +1,84 @@
+use color_shredder::engine::{
+    all_colors, ColorOrdering, ColorSource, Engine, Rgb, SeedPoint, Settings,
+};
+use std::collections::HashSet;
+
+fn seeded_engine(seed_points: Vec<SeedPoint>) -> Result<Engine, String> {
+    Engine::new(Settings {
+        source: ColorSource::Cube { bits: 2 },
+        ordering: ColorOrdering::Luminance,
+        seed_points,
+        ..Settings::new(8, 8, 7)
+    })
+}
+
+fn image_colors(engine: &Engine) -> HashSet<Rgb> {
+    (0..engine.cell_count())
+        .map(|cell| engine.cell_color(cell).unwrap())
+        .collect()
+}
+
+#[test]
+fn seed_points_are_placed_first_in_order() {
+    let mut engine = seeded_engine(vec![
+        SeedPoint::with_color(1, 2, [255, 0, 0]),
+        SeedPoint::new(6, 6),
+        SeedPoint::new(0, 7),
+    ])
+    .unwrap();
+
+    let first: Vec<_> = (0..3).map(|_| engine.step().unwrap()).collect();
+    assert_eq!(first[0].cell, 2 * 8 + 1);
+    assert_eq!(first[0].color, [255, 0, 0]);
+    assert_eq!(first[1].cell, 6 * 8 + 6);
+    assert_eq!(first[2].cell, 7 * 8);
+    // seeds without a color take the darkest colors of the luminance ordering
+    assert_eq!(first[1].color, [0, 0, 0]);
+    assert_eq!(first[2].color, [0, 0, 85]);
+
+    engine.run();
+    assert_eq!(engine.placed(), 64);
+    assert_eq!(image_colors(&engine), all_colors(2).into_iter().collect());
+}
+
+#[test]
+fn explicit_colors_replace_the_closest_source_color() {
+    let mut engine = seeded_engine(vec![SeedPoint::with_color(3, 3, [80, 90, 10])]).unwrap();
+    engine.run();
+
+    let colors = image_colors(&engine);
+    assert_eq!(engine.color_at(3, 3), Some([80, 90, 10]));
+    assert!(!colors.contains(&[85, 85, 0]));
+    assert_eq!(colors.len(), 64);
+}
+
+#[test]
+fn rejects_seed_points_outside_the_grid() {
+    let error = seeded_engine(vec![SeedPoint::new(0, 0), SeedPoint::new(8, 3)])
+        .err()
+        .unwrap();
+    assert_eq!(error, "seed point 1 at 8,3 lies outside the 8x8 grid");
+}
+
+#[test]
+fn rejects_overlapping_seed_points() {
+    let error = seeded_engine(vec![
+        SeedPoint::new(4, 4),
+        SeedPoint::new(1, 1),
+        SeedPoint::with_color(4, 4, [0, 0, 0]),
+    ])
+    .err()
+    .unwrap();
+    assert_eq!(error, "seed points 0 and 2 both sit at 4,4");
+}
+
+#[test]
+fn parses_seed_points() {
+    assert_eq!("12,5".parse::<SeedPoint>().unwrap(), SeedPoint::new(12, 5));
+    let colored = SeedPoint::with_color(0, 9, [255, 136, 0]);
+    assert_eq!("0, 9, #ff8800".parse::<SeedPoint>().unwrap(), colored);
+    assert_eq!(colored.to_string(), "0,9,#ff8800");
+    for spec in &["", "1", "1,2,3,4", "-1,2", "1,2,ff8800", "1,2,#ff88"] {
+        assert!(spec.parse::<SeedPoint>().is_err(), "{}", spec);
+    }
+}
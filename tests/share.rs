@@ -0,0 +1,132 @@
+use color_shredder::engine::{
+    decode_share_string, encode_share_string, CellMask, ColorOrdering, ColorSource, Engine, Mask,
+    Metric, Neighborhood, SeedPoint, Settings, Topology, MAX_SHARED_CELLS, SHARE_VERSION,
+};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+fn variants() -> Vec<Settings> {
+    let mut mask = Mask::new(64, 64);
+    for y in 0..64 {
+        for x in 0..y / 2 {
+            mask.set(x, y, CellMask::Forbidden);
+        }
+    }
+    mask.set(40, 40, CellMask::FillLast);
+    vec![
+        Settings::new(512, 512, 42),
+        Settings {
+            source: ColorSource::Cube { bits: 6 },
+            ordering: ColorOrdering::RandomHueBias { strength: 0.75 },
+            metric: Metric::Ciede2000,
+            neighborhood: Neighborhood::Custom(vec![(1, 1, 0.5), (-3, 0, 2.0)]),
+            topology: Topology::Mirrored,
+            seed_points: vec![SeedPoint::with_color(9, 9, [9, 99, 199])],
+            ..Settings::new(512, 512, u64::MAX)
+        },
+        Settings {
+            source: ColorSource::SampledCube {
+                count: mask.placeable_count(),
+            },
+            mask: Some(mask),
+            ..Settings::new(64, 64, 3)
+        },
+    ]
+}
+
+#[test]
+fn share_strings_restore_the_identical_run() {
+    for settings in variants() {
+        let text = encode_share_string(&settings);
+        assert!(text
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_eq!(decode_share_string(&text).unwrap(), settings);
+    }
+
+    // short enough for a link
+    assert!(encode_share_string(&variants()[0]).len() < 64);
+    assert!(encode_share_string(&variants()[2]).len() < 200);
+
+    let small = Settings::new(12, 8, 5);
+    let mut original = Engine::new(small.clone()).unwrap();
+    let mut shared =
+        Engine::new(decode_share_string(&encode_share_string(&small)).unwrap()).unwrap();
+    original.run();
+    shared.run();
+    assert_eq!(original.pixels(), shared.pixels());
+}
+
+#[test]
+fn rejects_malformed_strings_with_a_message() {
+    let text = encode_share_string(&Settings::new(16, 16, 1));
+    assert_eq!(
+        decode_share_string("not base64!").err().unwrap(),
+        "the share string is damaged, it is not base64url"
+    );
+    assert_eq!(
+        decode_share_string("").err().unwrap(),
+        "the share string is empty"
+    );
+
+    // a version from a later release
+    let newer = format!("Ag{}", &text[2..]);
+    assert_eq!(
+        decode_share_string(&newer).err().unwrap(),
+        format!(
+            "share string version 2 is not supported, expected {}",
+            SHARE_VERSION
+        )
+    );
+
+    for length in 1..text.len() {
+        assert!(decode_share_string(&text[..length]).is_err());
+    }
+
+    // a histogram of ([1, 2, 3], usize::MAX) and ([4, 5, 6], 2) for a 1x1
+    // grid, its counts overflow when summed
+    assert_eq!(
+        decode_share_string("AVXLSRIAAAQDQWL5_485oNDXZJgK0yeYARJNzbGPUyQ")
+            .err()
+            .unwrap(),
+        format!(
+            "histogram entry 0 holds {} colors but the grid has 1 cells",
+            usize::MAX
+        )
+    );
+}
+
+#[test]
+fn survives_random_damage() {
+    let mut rng = ChaCha8Rng::seed_from_u64(7);
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+    for settings in variants() {
+        let text = encode_share_string(&settings).into_bytes();
+        for _ in 0..300 {
+            let mut damaged = text.clone();
+            for _ in 0..rng.gen_range(1..4) {
+                let at = rng.gen_range(0..damaged.len());
+                damaged[at] = ALPHABET[rng.gen_range(0..ALPHABET.len())];
+            }
+            // errors are fine, panics are not
+            let _ = decode_share_string(std::str::from_utf8(&damaged).unwrap());
+        }
+    }
+}
+
+#[test]
+fn refuses_runs_too_large_to_share() {
+    let settings = Settings {
+        source: ColorSource::Histogram(vec![([1, 2, 3], 1 << 28)]),
+        ..Settings::new(1 << 14, 1 << 14, 0)
+    };
+    assert_eq!(
+        decode_share_string(&encode_share_string(&settings))
+            .err()
+            .unwrap(),
+        format!(
+            "shared runs are limited to {} cells, got 16384x16384",
+            MAX_SHARED_CELLS
+        )
+    );
+}
@@ -0,0 +1,92 @@
+use color_shredder::engine::{ColorSource, Engine, Settings};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::collections::HashSet;
+
+#[test]
+fn cube_yields_every_color_once() {
+    let colors = ColorSource::Cube { bits: 6 }.colors(&mut ChaCha8Rng::seed_from_u64(0));
+    assert_eq!(colors.len(), 512 * 512);
+    assert_eq!(colors.iter().collect::<HashSet<_>>().len(), colors.len());
+    assert_eq!(colors[0], [0, 0, 0]);
+    assert_eq!(colors[colors.len() - 1], [255, 255, 255]);
+}
+
+#[test]
+fn sampled_cube_is_distinct_and_seeded() {
+    let source = ColorSource::SampledCube { count: 1000 };
+    let sample = |seed| source.colors(&mut ChaCha8Rng::seed_from_u64(seed));
+
+    assert_eq!(sample(1).iter().collect::<HashSet<_>>().len(), 1000);
+    assert_eq!(sample(1), sample(1));
+    assert_ne!(sample(1), sample(2));
+}
+
+#[test]
+fn histogram_counts_opaque_pixels() {
+    let rgba = [
+        10, 20, 30, 255, //
+        10, 20, 30, 255, //
+        0, 0, 0, 0, //
+        1, 2, 3, 128,
+    ];
+    let source = ColorSource::from_rgba(&rgba).unwrap();
+    assert_eq!(
+        source,
+        ColorSource::Histogram(vec![([1, 2, 3], 1), ([10, 20, 30], 2)])
+    );
+    assert_eq!(source.count(), Ok(3));
+    assert!(ColorSource::from_rgba(&rgba[..5]).is_err());
+}
+
+#[test]
+fn palette_duplicates_are_placed_as_often_as_listed() {
+    let settings = Settings {
+        source: ColorSource::Palette(vec![[255, 0, 0], [255, 0, 0], [0, 0, 255], [0, 255, 0]]),
+        ..Settings::new(2, 2, 5)
+    };
+    let mut engine = Engine::new(settings).unwrap();
+    engine.run();
+
+    let reds = (0..4)
+        .filter(|&cell| engine.cell_color(cell) == Some([255, 0, 0]))
+        .count();
+    assert_eq!(reds, 2);
+}
+
+#[test]
+fn count_mismatches_are_described() {
+    let check = |source: ColorSource| source.check(16).err().unwrap();
+
+    assert_eq!(
+        check(ColorSource::Palette(vec![[0, 0, 0]; 3])),
+        "a palette of 3 entries yields 3 colors but the grid has 16 cells"
+    );
+    assert_eq!(
+        check(ColorSource::Histogram(vec![([0, 0, 0], 10), ([1, 1, 1], 10)])),
+        "an image histogram of 2 distinct colors yields 20 colors but the grid has 16 cells"
+    );
+    assert_eq!(
+        check(ColorSource::Histogram(vec![([0, 0, 0], 1), ([1, 1, 1], 20)])),
+        "histogram entry 1 holds 20 colors but the grid has 16 cells"
+    );
+    assert_eq!(
+        check(ColorSource::Cube { bits: 0 }),
+        "a color cube needs 1 to 8 bits per channel, got 0"
+    );
+    assert!(ColorSource::SampledCube { count: 16 }.check(16).is_ok());
+}
+
+#[test]
+fn counts_that_overflow_are_errors() {
+    let histogram = ColorSource::Histogram(vec![([1, 2, 3], usize::MAX), ([4, 5, 6], 2)]);
+    assert_eq!(
+        histogram.count().err().unwrap(),
+        "histogram entry 1 makes the color count too large"
+    );
+    assert!(histogram.check(usize::MAX).is_err());
+    assert_eq!(
+        ColorSource::Cube { bits: u32::MAX }.count().err().unwrap(),
+        format!("a color cube of {} bits has too many colors", u32::MAX)
+    );
+}
@@ -0,0 +1,163 @@
+use color_shredder::engine::{
+    ColorMetric, ColorSource, Engine, Metric, Neighborhood, Settings, Topology,
+};
+use std::collections::BTreeSet;
+
+fn cube_engine(topology: Topology, seed: u64) -> Engine {
+    let settings = Settings {
+        source: ColorSource::Cube { bits: 4 },
+        topology,
+        ..Settings::new(64, 64, seed)
+    };
+    Engine::new(settings).unwrap()
+}
+
+// mean distance between horizontally adjacent cells, split into pairs across
+// the left/right seam and pairs inside the grid
+fn seam_and_interior_distance(engine: &Engine, metric: &dyn ColorMetric) -> (f64, f64) {
+    let (width, height) = (engine.width(), engine.height());
+    let distance = |x1, x2, y| {
+        metric.color_distance(
+            engine.color_at(x1, y).unwrap(),
+            engine.color_at(x2, y).unwrap(),
+        )
+    };
+    let seam: f64 = (0..height).map(|y| distance(width - 1, 0, y)).sum();
+    let interior: f64 = (0..height)
+        .flat_map(|y| (0..width - 1).map(move |x| (x, y)))
+        .map(|(x, y)| distance(x, x + 1, y))
+        .sum();
+    (
+        seam / height as f64,
+        interior / ((width - 1) * height) as f64,
+    )
+}
+
+#[test]
+fn horizontally_wrapped_results_are_continuous_across_the_seam() {
+    let metric = Metric::Rgb.implementation();
+    for seed in 0..3 {
+        let mut wrapped = cube_engine(Topology::WrapHorizontal, seed);
+        wrapped.run();
+        let (seam, interior) = seam_and_interior_distance(&wrapped, metric);
+        assert!(
+            seam < 1.5 * interior,
+            "seed {}: seam {} interior {}",
+            seed,
+            seam,
+            interior
+        );
+
+        // without wrapping the edges are unrelated
+        let mut bounded = cube_engine(Topology::Bounded, seed);
+        bounded.run();
+        let (seam, interior) = seam_and_interior_distance(&bounded, metric);
+        assert!(
+            seam > 2.0 * interior,
+            "seed {}: seam {} interior {}",
+            seed,
+            seam,
+            interior
+        );
+    }
+}
+
+// the topologies spelled out independently of the engine
+fn resolve(topology: Topology, x: isize, y: isize, width: isize, height: isize) -> Option<usize> {
+    let wrap = |coord: isize, size: isize| coord.rem_euclid(size);
+    let mirror = |coord: isize, size: isize| {
+        if coord < 0 {
+            -coord
+        } else if coord >= size {
+            2 * (size - 1) - coord
+        } else {
+            coord
+        }
+    };
+    let (x, y) = match topology {
+        Topology::Bounded => (x, y),
+        Topology::WrapHorizontal => (wrap(x, width), y),
+        Topology::WrapVertical => (x, wrap(y, height)),
+        Topology::Torus => (wrap(x, width), wrap(y, height)),
+        Topology::Mirrored => (mirror(x, width), mirror(y, height)),
+    };
+    if (0..width).contains(&x) && (0..height).contains(&y) {
+        Some((y * width + x) as usize)
+    } else {
+        None
+    }
+}
+
+fn expected_frontier(engine: &Engine, topology: Topology) -> BTreeSet<usize> {
+    let (width, height) = (engine.width() as isize, engine.height() as isize);
+    let kernel = engine.settings().neighborhood.kernel();
+    (0..engine.cell_count())
+        .filter(|&cell| engine.cell_color(cell).is_none())
+        .filter(|&cell| {
+            let (x, y) = (cell as isize % width, cell as isize / width);
+            kernel.offsets(y as usize).iter().any(|&(dx, dy, _)| {
+                resolve(topology, x + dx, y + dy, width, height)
+                    .is_some_and(|neighbor| engine.cell_color(neighbor).is_some())
+            })
+        })
+        .collect()
+}
+
+#[test]
+fn frontier_follows_the_topology() {
+    let topologies = [
+        Topology::Bounded,
+        Topology::WrapHorizontal,
+        Topology::WrapVertical,
+        Topology::Torus,
+        Topology::Mirrored,
+    ];
+    let neighborhoods = [
+        Neighborhood::Moore,
+        Neighborhood::Hexagonal,
+        Neighborhood::Disk { radius: 2 },
+        Neighborhood::Custom(vec![(2, 0, 1.0), (0, -1, 1.0)]),
+    ];
+    for &topology in &topologies {
+        for neighborhood in &neighborhoods {
+            let settings = Settings {
+                neighborhood: neighborhood.clone(),
+                topology,
+                ..Settings::new(7, 6, 3)
+            };
+            let mut engine = Engine::new(settings).unwrap();
+            while engine.step().is_some() {
+                let frontier: BTreeSet<usize> = engine.frontier().iter().collect();
+                assert_eq!(
+                    frontier,
+                    expected_frontier(&engine, topology),
+                    "{} {} after {} placements",
+                    topology,
+                    neighborhood,
+                    engine.placed()
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn names_round_trip() {
+    for name in &["bounded", "wrap-x", "wrap-y", "torus", "mirror"] {
+        assert_eq!(name.parse::<Topology>().unwrap().to_string(), *name);
+    }
+    assert!("sphere".parse::<Topology>().is_err());
+}
+
+#[test]
+fn rejects_odd_height_hexagonal_wrap() {
+    let settings = Settings {
+        neighborhood: Neighborhood::Hexagonal,
+        topology: Topology::Torus,
+        ..Settings::new(4, 5, 0)
+    };
+    assert_eq!(
+        Engine::new(settings).err().unwrap(),
+        "a hexagonal grid needs an even height to wrap vertically, got 5"
+    );
+}
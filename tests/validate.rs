@@ -0,0 +1,157 @@
+use color_shredder::engine::{
+    validate_pixels, validate_png, CellMask, ColorSource, Engine, Mask, SeedPoint, Settings,
+};
+
+fn finished(settings: &Settings) -> Engine {
+    let mut engine = Engine::new(settings.clone()).unwrap();
+    engine.run();
+    engine
+}
+
+#[test]
+fn finished_runs_are_valid() {
+    let mut mask = Mask::new(10, 10);
+    mask.set(4, 4, CellMask::Forbidden);
+    mask.set(0, 0, CellMask::FillLast);
+    let variants = vec![
+        Settings::new(8, 8, 1),
+        Settings {
+            source: ColorSource::SampledCube { count: 300 },
+            ..Settings::new(20, 15, 2)
+        },
+        Settings {
+            source: ColorSource::Palette(vec![[1, 2, 3], [1, 2, 3], [9, 9, 9], [0, 0, 0]]),
+            seed_points: vec![SeedPoint::with_color(1, 1, [9, 9, 9])],
+            ..Settings::new(2, 2, 3)
+        },
+        Settings {
+            source: ColorSource::SampledCube { count: 99 },
+            mask: Some(mask),
+            ..Settings::new(10, 10, 4)
+        },
+    ];
+    for settings in variants {
+        let engine = finished(&settings);
+        let report = engine.validate();
+        assert!(report.is_valid(), "{:?}: {}", settings, report);
+        assert_eq!(validate_pixels(&settings, engine.pixels()).unwrap(), report);
+        assert_eq!(
+            validate_png(&settings, &engine.encode_png(true).unwrap()).unwrap(),
+            report
+        );
+    }
+}
+
+#[test]
+fn reports_duplicates_missing_and_unfilled_cells() {
+    let settings = Settings::new(8, 8, 5);
+    let mut engine = Engine::new(settings.clone()).unwrap();
+    engine.step_n(60);
+    let report = engine.validate();
+    assert!(!report.is_valid());
+    assert_eq!(report.unfilled.len(), 4);
+    assert_eq!(report.missing.len(), 4);
+    assert!(report.duplicates.is_empty());
+
+    engine.run();
+    let mut pixels = engine.pixels().to_vec();
+    let copied = [pixels[0], pixels[1], pixels[2]];
+    let replaced = [pixels[4], pixels[5], pixels[6]];
+    pixels[4..7].copy_from_slice(&copied);
+    // an empty cell
+    pixels[11] = 0;
+    let lost = [pixels[8], pixels[9], pixels[10]];
+
+    let report = validate_pixels(&settings, &pixels).unwrap();
+    assert_eq!(report.duplicates, vec![(copied, 1)]);
+    let mut missing = vec![(replaced, 1), (lost, 1)];
+    missing.sort_unstable();
+    assert_eq!(report.missing, missing);
+    assert_eq!(report.unfilled, vec![2]);
+    assert!(report.forbidden_filled.is_empty());
+    assert_eq!(
+        report.to_string(),
+        format!(
+            "1 duplicate colors (1 placements): {}\n\
+             2 missing colors (2 placements): {}, {}\n\
+             1 unfilled cells: 2",
+            hex(copied),
+            hex(missing[0].0),
+            hex(missing[1].0)
+        )
+    );
+}
+
+fn hex([r, g, b]: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x} x1", r, g, b)
+}
+
+#[test]
+fn reports_seed_colors_missing_from_the_source() {
+    let settings = Settings {
+        source: ColorSource::Palette(vec![[1, 2, 3], [1, 2, 3], [9, 9, 9], [0, 0, 0]]),
+        seed_points: vec![SeedPoint::with_color(1, 1, [8, 8, 8])],
+        ..Settings::new(2, 2, 3)
+    };
+    let engine = finished(&settings);
+    let report = engine.validate();
+    assert!(!report.is_valid());
+    assert_eq!(report.substituted, vec![([8, 8, 8], [9, 9, 9])]);
+    assert!(report.duplicates.is_empty());
+    assert!(report.missing.is_empty());
+    assert_eq!(
+        report.to_string(),
+        "1 seed colors not in the source: #080808 for #090909"
+    );
+    assert_eq!(validate_pixels(&settings, engine.pixels()).unwrap(), report);
+
+    // without the seed points the same image has a surplus and a shortfall
+    let bare = Settings {
+        seed_points: Vec::new(),
+        ..settings
+    };
+    let report = validate_pixels(&bare, engine.pixels()).unwrap();
+    assert_eq!(report.duplicates, vec![([8, 8, 8], 1)]);
+    assert_eq!(report.missing, vec![([9, 9, 9], 1)]);
+    assert!(report.substituted.is_empty());
+}
+
+#[test]
+fn reports_colors_in_forbidden_cells() {
+    let mut mask = Mask::new(4, 4);
+    mask.set(3, 3, CellMask::Forbidden);
+    let settings = Settings {
+        source: ColorSource::SampledCube { count: 15 },
+        mask: Some(mask),
+        ..Settings::new(4, 4, 6)
+    };
+    let engine = finished(&settings);
+    let mut pixels = engine.pixels().to_vec();
+    pixels[60..64].copy_from_slice(&[1, 2, 3, 255]);
+
+    let report = validate_pixels(&settings, &pixels).unwrap();
+    assert_eq!(report.forbidden_filled, vec![15]);
+    assert_eq!(report.duplicates, vec![([1, 2, 3], 1)]);
+    assert!(report.missing.is_empty());
+    assert!(report.unfilled.is_empty());
+}
+
+#[test]
+fn rejects_images_of_the_wrong_size() {
+    let settings = Settings::new(8, 8, 7);
+    let other = finished(&Settings {
+        source: ColorSource::SampledCube { count: 32 },
+        ..Settings::new(8, 4, 7)
+    });
+    assert_eq!(
+        validate_png(&settings, &other.encode_png(false).unwrap())
+            .err()
+            .unwrap(),
+        "the image is 8x4 but the grid is 8x8"
+    );
+    assert_eq!(
+        validate_pixels(&settings, other.pixels()).err().unwrap(),
+        "image of 128 bytes does not match the 8x8 grid"
+    );
+    assert!(validate_png(&settings, b"not a png").is_err());
+}
@@ -0,0 +1,38 @@
+// runs the placement engine off the main thread. messages in both directions
+// are the byte encoded requests and responses of src/engine/protocol.rs
+
+// milliseconds of placement work per posted batch
+const BATCH_BUDGET_MS = 16.0;
+
+// requests that arrive while the wasm module is still loading
+const pending = [];
+onmessage = event => pending.push(event.data);
+
+import('./pkg').then(wasm => {
+    const worker = new wasm.PlacementWorker();
+    let scheduled = false;
+
+    const post = message => {
+        if (message.length > 0) {
+            postMessage(message.buffer, [message.buffer]);
+        }
+    };
+    const schedule = () => {
+        if (worker.is_running() && !scheduled) {
+            scheduled = true;
+            setTimeout(run, 0);
+        }
+    };
+    const run = () => {
+        scheduled = false;
+        post(worker.step(BATCH_BUDGET_MS));
+        schedule();
+    };
+    const handle = data => {
+        post(worker.handle(new Uint8Array(data)));
+        schedule();
+    };
+
+    onmessage = event => handle(event.data);
+    pending.forEach(handle);
+}).catch(console.error);